@@ -1,21 +1,70 @@
 use crate::configuration::settings::Settings;
+use crate::download::download;
+use core::fmt::Display;
+use core::mem;
+use core::str::FromStr;
 use itertools::Itertools;
-use plotters_canvas::CanvasBackend;
+use plotters::prelude::DrawingBackend;
 use splines::{Key, Spline};
-use web_sys::HtmlCanvasElement;
+use strum::IntoEnumIterator;
+use web_sys::{Element, HtmlCanvasElement};
 use yew::prelude::*;
 
+mod animation;
+mod cache;
+mod comparison;
 mod cummulative;
 mod current;
+mod diagnostics;
+mod export;
+mod heatmap;
+mod layout;
+mod limits;
+mod overlay;
+mod reference;
+mod surface;
+mod zoom;
 
+use animation::{AnimationOptions, SetAnimation};
+use cache::{CacheKey, Values};
+use comparison::Comparison;
 use cummulative::Cummulative;
 use current::Current;
+use diagnostics::{Diagnostics, DiagnosticsPlot, Quantity};
+use export::{to_png, to_svg, ExportFormat, ImageFormat, Plot, Results};
+use heatmap::{Colormap, HeatmapOptions, Scaling, SetHeatmap};
+use layout::{Layout, PixelRatioListener, ResizeListener, ScaledCanvas};
+use limits::{Autoscale, Limits};
+use overlay::{OverlayOptions, SetOverlay};
+use reference::{Reference, ReferenceError};
+use surface::{SetViewpoint, Surface, Viewpoint};
+use zoom::{Coordinates, Pointer, Zoom, ZOOM_FACTOR};
 
 #[derive(Debug)]
 pub enum Msg {
     RestartFrom(Settings),
     Time(ChangeData),
     Advance,
+    Export(ExportFormat),
+    ExportPlot(Plot, ImageFormat),
+    Animation(SetAnimation),
+    ExportAnimation,
+    Heatmap(SetHeatmap),
+    Autoscale(ChangeData),
+    Overlay(SetOverlay),
+    Reference(ChangeData),
+    Quantity(ChangeData),
+    Viewpoint(SetViewpoint),
+    Pointer(Plot, Pointer),
+    ResetZoom,
+    /// The plots have the given width available.
+    Resize(f64),
+    /// The density of the screen changed.
+    PixelRatio,
+    /// Values computed before from the same settings were found in the cache.
+    Restored(CacheKey, Values),
+    /// The given number of time slices are in the cache, or `None` if they could not be saved.
+    Saved(CacheKey, Option<usize>),
 }
 
 #[derive(Debug)]
@@ -23,11 +72,44 @@ pub struct Analysis {
     link: ComponentLink<Self>,
     current: Current,
     cummulative: Cummulative,
+    comparison: Comparison,
+    diagnostics: DiagnosticsPlot,
+    surface: Surface,
+    viewpoint: Viewpoint,
+    /// Quantity plotted by `diagnostics`.
+    quantity: Quantity,
     values: Vec<Spline<f64, f64>>,
     current_time: usize,
     max_time: usize,
-    limit_values: (f64, f64),
+    /// Limits of the computed values.
+    limits: Limits,
+    autoscale: Autoscale,
+    overlay: OverlayOptions,
+    /// Solution to compare with.
+    reference: Option<Reference>,
+    reference_string: String,
+    reference_error: Option<ReferenceError>,
     settings: Settings,
+    animation: AnimationOptions,
+    heatmap: HeatmapOptions,
+    /// Key of the current settings in the cache, if they could be hashed.
+    cache_key: Option<CacheKey>,
+    /// Whether values are being saved in the cache.
+    saving: bool,
+    /// Number of time slices saved in the cache.
+    saved: usize,
+    zoom: Zoom,
+    current_coordinates: Option<Coordinates>,
+    cummulative_coordinates: Option<Coordinates>,
+    /// Values under the pointer.
+    readout: Option<String>,
+    /// Whether the plots changed since they were last drawn.
+    replot: bool,
+    /// Element whose width the plots fill.
+    container_ref: NodeRef,
+    layout: Layout,
+    resize_listener: Option<ResizeListener>,
+    pixel_ratio_listener: Option<PixelRatioListener>,
 }
 
 impl Component for Analysis {
@@ -41,11 +123,35 @@ impl Component for Analysis {
             link,
             current,
             cummulative,
+            comparison: Comparison::new(NodeRef::default()),
+            diagnostics: DiagnosticsPlot::new(NodeRef::default()),
+            surface: Surface::new(NodeRef::default()),
+            viewpoint: Viewpoint::default(),
+            quantity: Quantity::Integral,
             values: vec![],
             current_time: 0,
             max_time: 0,
-            limit_values: (0., 0.),
+            limits: Limits::new(&[]),
+            autoscale: Autoscale::default(),
+            overlay: OverlayOptions::default(),
+            reference: None,
+            reference_string: String::new(),
+            reference_error: None,
             settings: Settings::default(), // they are updated anyway
+            animation: AnimationOptions::default(),
+            heatmap: HeatmapOptions::default(),
+            cache_key: None,
+            saving: false,
+            saved: 0,
+            zoom: Zoom::default(),
+            current_coordinates: None,
+            cummulative_coordinates: None,
+            readout: None,
+            replot: true,
+            container_ref: NodeRef::default(),
+            layout: Layout::default(),
+            resize_listener: None,
+            pixel_ratio_listener: None,
         }
     }
 
@@ -56,8 +162,7 @@ impl Component for Analysis {
                     log::trace!("Trying to change time to {}", s);
                     let proposal: usize = s.parse().unwrap();
                     self.current_time = proposal;
-                    // TODO: Update current plot
-                    true
+                    self.replot(true)
                 } else {
                     log::error!("Tried to change time to {:?}", data);
                     false
@@ -72,25 +177,129 @@ impl Component for Analysis {
                     self.compute_next();
                     self.current_time += 1;
                     self.max_time = self.current_time.max(self.max_time);
+                    self.save();
+                }
+                self.replot(true)
+            }
+            Msg::Export(format) => {
+                log::trace!("Exporting values as {}", format);
+                if let Err(e) = self.export(format) {
+                    log::error!("Could not export values: {}", e);
+                }
+                false
+            }
+            Msg::ExportPlot(plot, format) => {
+                log::trace!("Exporting {} plot as {}", plot, format);
+                if let Err(e) = self.export_plot(plot, format) {
+                    log::error!("Could not export plot: {}", e);
+                }
+                false
+            }
+            Msg::Animation(set) => self.animation.update(set),
+            Msg::Heatmap(set) => {
+                let changed = self.heatmap.update(set);
+                self.replot(changed)
+            }
+            Msg::Overlay(set) => {
+                let changed = self.overlay.update(set);
+                self.replot(changed)
+            }
+            Msg::Viewpoint(set) => {
+                let changed = self.viewpoint.update(set);
+                self.replot(changed)
+            }
+            Msg::Pointer(plot, pointer) => self.pointer(plot, pointer),
+            Msg::ResetZoom => {
+                self.zoom = Zoom::default();
+                self.replot(true)
+            }
+            Msg::Resize(width) => self.relayout(width),
+            Msg::PixelRatio => self.relayout(self.layout.width),
+            Msg::Quantity(data) => match select(data) {
+                Some(quantity) => {
+                    log::trace!("Changing diagnostics to {}", quantity);
+                    self.quantity = quantity;
+                    self.replot(true)
+                }
+                None => false,
+            },
+            Msg::Reference(data) => {
+                if let ChangeData::Value(s) = data {
+                    log::trace!("Trying to change reference to {}", s);
+                    self.reference_string = s.clone();
+                    if s.trim().is_empty() {
+                        self.reference = None;
+                        self.reference_error = None;
+                    } else {
+                        match s.parse() {
+                            Ok(reference) => {
+                                self.reference = Some(reference);
+                                self.reference_error = None;
+                            }
+                            Err(e) => self.reference_error = Some(e),
+                        }
+                    }
+                    self.replot(true)
+                } else {
+                    log::error!("Tried to change reference to {:?}", data);
+                    false
                 }
-                true
+            }
+            Msg::Autoscale(data) => match select(data) {
+                Some(autoscale) => {
+                    log::trace!("Changing autoscale to {}", autoscale);
+                    self.autoscale = autoscale;
+                    self.replot(true)
+                }
+                None => false,
+            },
+            Msg::ExportAnimation => {
+                log::trace!("Exporting animation of {} frames", self.values.len());
+                if let Err(e) = self.export_animation() {
+                    log::error!("Could not export animation: {}", e);
+                }
+                false
             }
             Msg::RestartFrom(settings) => {
                 log::trace!("Restarting from new settings");
                 let spline = settings.compute_initial_spline();
-                self.limit_values = spline
-                    .keys()
-                    .iter()
-                    .map(|k| k.value)
-                    .minmax()
-                    .into_option()
-                    .unwrap();
                 self.values = vec![spline];
+                self.limits = Limits::new(&self.values);
+                self.cache_key = CacheKey::new(&settings)
+                    .map_err(|e| log::error!("Could not hash the settings: {}", e))
+                    .ok();
                 self.settings = settings;
+                self.zoom = Zoom::default();
                 self.current_time = 0;
                 self.max_time = 0;
+                self.saved = 0;
+                self.restore();
 
-                true
+                self.replot(true)
+            }
+            Msg::Restored(key, values) => {
+                if self.cache_key.as_ref() == Some(&key) && values.len() > self.values.len() {
+                    log::trace!("Restoring {} time slices from the cache", values.len());
+                    self.max_time = values.len() - 1;
+                    self.saved = values.len();
+                    self.values = values;
+                    self.limits = Limits::new(&self.values);
+                    self.replot(true)
+                } else {
+                    false
+                }
+            }
+            Msg::Saved(key, saved) => {
+                self.saving = false;
+                if self.cache_key.as_ref() == Some(&key) {
+                    match saved {
+                        Some(saved) => self.saved = saved,
+                        // Stop caching these values rather than failing again at each step
+                        None => self.cache_key = None,
+                    }
+                }
+                self.save();
+                false
             }
         }
     }
@@ -101,71 +310,489 @@ impl Component for Analysis {
 
     fn view(&self) -> Html {
         html! {
-            <p>
+            <p ref=self.container_ref.clone()>
                 { "Analysis" }
                 <div>
-                    <canvas ref={self.cummulative.canvas_ref.clone()} />
-                    <canvas ref={self.current.canvas_ref.clone()} />
+                    <canvas
+                        ref={self.cummulative.canvas_ref.clone()}
+                        onmousemove=self.link.callback(|e| Msg::Pointer(Plot::Cummulative, Pointer::Move(e)))
+                        onmousedown=self.link.callback(|e| Msg::Pointer(Plot::Cummulative, Pointer::Down(e)))
+                        onmouseup=self.link.callback(|e| Msg::Pointer(Plot::Cummulative, Pointer::Up(e)))
+                        onmouseleave=self.link.callback(|_| Msg::Pointer(Plot::Cummulative, Pointer::Leave))
+                        onwheel=self.link.callback(|e| Msg::Pointer(Plot::Cummulative, Pointer::Wheel(e)))
+                    />
+                    <canvas
+                        ref={self.current.canvas_ref.clone()}
+                        onmousemove=self.link.callback(|e| Msg::Pointer(Plot::Current, Pointer::Move(e)))
+                        onmousedown=self.link.callback(|e| Msg::Pointer(Plot::Current, Pointer::Down(e)))
+                        onmouseup=self.link.callback(|e| Msg::Pointer(Plot::Current, Pointer::Up(e)))
+                        onmouseleave=self.link.callback(|_| Msg::Pointer(Plot::Current, Pointer::Leave))
+                        onwheel=self.link.callback(|e| Msg::Pointer(Plot::Current, Pointer::Wheel(e)))
+                    />
+                    { if self.reference.is_some() {
+                        html! { <canvas ref={self.comparison.canvas_ref.clone()} /> }
+                    } else {
+                        html! {}
+                    } }
+                </div>
+                <div>
+                    { self.readout.clone().unwrap_or_default() }
+                    <button onclick=self.link.callback(|_| Msg::ResetZoom) disabled=self.zoom.range.is_none()>{ "Reset zoom" }</button>
+                </div>
+                <div>
+                    <canvas ref={self.surface.canvas_ref.clone()} />
+                    <br/>
+                    { "Yaw" }
+                    <input type="range" id="yaw" name="yaw" min="-180" max="180" value=self.viewpoint.yaw.to_string() class="slider" onchange=self.link.callback(|x| Msg::Viewpoint(SetViewpoint::Yaw(x)))/>
+                    { "Pitch" }
+                    <input type="range" id="pitch" name="pitch" min="-90" max="90" value=self.viewpoint.pitch.to_string() class="slider" onchange=self.link.callback(|x| Msg::Viewpoint(SetViewpoint::Pitch(x)))/>
                 </div>
                 <div>
                     { "Time" }
-                    <input type="range" id="time" name="time" min="0" max=self.max_time.to_string() value=self.current_time.to_string() class="slider" onchange=self.link.callback(|x| Msg::Time(x))/>
+                    <input type="range" id="time" name="time" min="0" max=self.max_time.to_string() value=self.current_time.to_string() class="slider" onchange=self.link.callback(Msg::Time)/>
                     <button onclick=self.link.callback(|_| Msg::Advance)>{ "Advance" }</button>
                 </div>
+                <div>
+                    { "Limits" }
+                    <select name="autoscale" id="autoscale" onchange=self.link.callback(Msg::Autoscale)>
+                        { for Autoscale::iter().map(|autoscale| html! {
+                            <option value=autoscale.to_string() selected=self.autoscale == autoscale>{ autoscale.to_string() }</option>
+                        }) }
+                    </select>
+                </div>
+                <div>
+                    { "Show initial" }
+                    <input type="checkbox" id="overlay_initial" name="overlay_initial" checked=self.overlay.initial onchange=self.link.callback(|x| Msg::Overlay(SetOverlay::Initial(x)))/>
+                    { "Previous times" }
+                    <input type="number" id="overlay_previous" name="overlay_previous" min="0" value=self.overlay.previous.to_string() onchange=self.link.callback(|x| Msg::Overlay(SetOverlay::Previous(x)))/>
+                </div>
+                <div>
+                    { "Reference u(x, t)" }
+                    <input
+                        type="text"
+                        id="reference"
+                        name="reference"
+                        placeholder="exp(-{t}) * sin({x})"
+                        value=self.reference_string.clone()
+                        onchange=self.link.callback(Msg::Reference)
+                    />
+                    { match &self.reference_error {
+                        Some(e) => html! { <span class="error">{ e.to_string() }</span> },
+                        None => html! {},
+                    } }
+                </div>
+                <div>
+                    { "Colormap" }
+                    <select name="colormap" id="colormap" onchange=self.link.callback(|x| Msg::Heatmap(SetHeatmap::Colormap(x)))>
+                        { for Colormap::iter().map(|colormap| html! {
+                            <option value=colormap.to_string() selected=self.heatmap.colormap == colormap>{ colormap.to_string() }</option>
+                        }) }
+                    </select>
+                    { "Current time" }
+                    <select name="highlight" id="highlight" onchange=self.link.callback(|x| Msg::Heatmap(SetHeatmap::Highlight(x)))>
+                        { for Colormap::iter().map(|colormap| html! {
+                            <option value=colormap.to_string() selected=self.heatmap.highlight == colormap>{ colormap.to_string() }</option>
+                        }) }
+                    </select>
+                    { "Scaling" }
+                    <select name="scaling" id="scaling" onchange=self.link.callback(|x| Msg::Heatmap(SetHeatmap::Scaling(x)))>
+                        { for Scaling::iter().map(|scaling| html! {
+                            <option value=scaling.to_string() selected=self.heatmap.scaling == scaling>{ scaling.to_string() }</option>
+                        }) }
+                    </select>
+                    { "Min" }
+                    <input type="number" id="heatmap_min" name="heatmap_min" placeholder="auto" value=self.heatmap.min.map(|x| x.to_string()).unwrap_or_default() onchange=self.link.callback(|x| Msg::Heatmap(SetHeatmap::Min(x)))/>
+                    { "Max" }
+                    <input type="number" id="heatmap_max" name="heatmap_max" placeholder="auto" value=self.heatmap.max.map(|x| x.to_string()).unwrap_or_default() onchange=self.link.callback(|x| Msg::Heatmap(SetHeatmap::Max(x)))/>
+                </div>
+                <div>
+                    { "Diagnostics" }
+                    <select name="quantity" id="quantity" onchange=self.link.callback(Msg::Quantity)>
+                        { for Quantity::iter().map(|quantity| html! {
+                            <option value=quantity.to_string() selected=self.quantity == quantity>{ quantity.to_string() }</option>
+                        }) }
+                    </select>
+                    <br/>
+                    <canvas ref={self.diagnostics.canvas_ref.clone()} />
+                    { self.view_diagnostics() }
+                </div>
+                <div>
+                    { "Export values" }
+                    { for ExportFormat::iter().map(|format| html! {
+                        <button onclick=self.link.callback(move |_| Msg::Export(format))>{ format.to_string() }</button>
+                    }) }
+                </div>
+                <div>
+                    { "Export plots" }
+                    { for Plot::iter().cartesian_product(ImageFormat::iter()).map(|(plot, format)| html! {
+                        <button onclick=self.link.callback(move |_| Msg::ExportPlot(plot, format))>{ format!("{} ({})", plot, format) }</button>
+                    }) }
+                </div>
+                <div>
+                    { "Export animation" }
+                    { "Frame delay (ms)" }
+                    <input type="number" id="animation_delay" name="animation_delay" min="0" value=self.animation.delay.to_string() onchange=self.link.callback(|x| Msg::Animation(SetAnimation::Delay(x)))/>
+                    { "Width" }
+                    <input type="number" id="animation_width" name="animation_width" min="1" value=self.animation.size.0.to_string() onchange=self.link.callback(|x| Msg::Animation(SetAnimation::Width(x)))/>
+                    { "Height" }
+                    <input type="number" id="animation_height" name="animation_height" min="1" value=self.animation.size.1.to_string() onchange=self.link.callback(|x| Msg::Animation(SetAnimation::Height(x)))/>
+                    <button onclick=self.link.callback(|_| Msg::ExportAnimation)>{ "GIF" }</button>
+                </div>
             </p>
         }
     }
 
     fn rendered(&mut self, first_render: bool) {
-        let current_canvas: HtmlCanvasElement =
-            self.current.canvas_ref.cast::<HtmlCanvasElement>().unwrap();
-        let cummulative_canvas: HtmlCanvasElement = self
-            .cummulative
-            .canvas_ref
-            .cast::<HtmlCanvasElement>()
-            .unwrap();
-
         if first_render {
             log::trace!("First render of Analysis");
-            current_canvas.set_width(self.settings.canvas_size.0);
-            current_canvas.set_height(self.settings.canvas_size.1);
-            cummulative_canvas.set_width(self.settings.canvas_size.0);
-            cummulative_canvas.set_height(self.settings.canvas_size.1);
-        } else {
+            let link = self.link.clone();
+            self.resize_listener = self.container_ref.cast::<Element>().and_then(|element| {
+                ResizeListener::new(&element, move |width| link.send_message(Msg::Resize(width)))
+            });
+            if self.resize_listener.is_none() {
+                log::warn!("Could not follow the size of the page, the plots keep their size");
+            }
+        }
+        if self.pixel_ratio_listener.as_ref().map(|l| l.pixel_ratio)
+            != Some(self.layout.pixel_ratio)
+        {
+            let link = self.link.clone();
+            self.pixel_ratio_listener =
+                PixelRatioListener::new(self.layout.pixel_ratio, move || {
+                    link.send_message(Msg::PixelRatio)
+                });
+        }
+        self.resize_canvases();
+
+        // There is nothing to plot before the first settings arrive
+        if !first_render && !self.values.is_empty() && mem::take(&mut self.replot) {
             log::trace!("Rerendering Analysis");
-            let current_backend: CanvasBackend =
-                CanvasBackend::with_canvas_object(current_canvas).unwrap();
-            self.current.plot(
-                current_backend,
-                self.current_time,
-                self.settings.domain,
-                self.limit_values,
-                &self.values[self.current_time],
+            let current_backend = self.backend(&self.current.canvas_ref).unwrap();
+            self.current_coordinates = Some(
+                self.plot_current(current_backend, self.current_time)
+                    .unwrap(),
             );
-            let cummulative_backend: CanvasBackend =
-                CanvasBackend::with_canvas_object(cummulative_canvas).unwrap();
-            self.cummulative
-                .plot(
-                    cummulative_backend,
+            let cummulative_backend = self.backend(&self.cummulative.canvas_ref).unwrap();
+            self.cummulative_coordinates =
+                Some(self.plot_cummulative(cummulative_backend).unwrap());
+            let surface_backend = self.backend(&self.surface.canvas_ref).unwrap();
+            if let Err(e) = self.plot_surface(surface_backend) {
+                log::error!("Could not plot the surface: {}", e);
+            }
+            let diagnostics_backend = self.backend(&self.diagnostics.canvas_ref).unwrap();
+            let diagnostics: Vec<Diagnostics> = self.values.iter().map(Diagnostics::new).collect();
+            if let Err(e) = self.diagnostics.plot(
+                diagnostics_backend,
+                self.current_time,
+                self.settings.kernel.time_step(),
+                self.quantity,
+                &diagnostics,
+            ) {
+                log::error!("Could not plot the diagnostics: {}", e);
+            }
+            if let (Some(reference), Some(comparison_backend)) =
+                (&self.reference, self.backend(&self.comparison.canvas_ref))
+            {
+                if let Err(e) = self.comparison.plot(
+                    comparison_backend,
                     self.current_time,
-                    self.limit_values,
+                    self.settings.kernel.time_step(),
+                    reference,
                     &self.values,
-                )
-                .unwrap();
+                ) {
+                    log::error!("Could not plot the comparison: {}", e);
+                }
+            }
         }
     }
 }
 
 impl Analysis {
+    /// Displays all quantities at the current time.
+    fn view_diagnostics(&self) -> Html {
+        match self.values.get(self.current_time) {
+            Some(spline) => {
+                let diagnostics = Diagnostics::new(spline);
+                html! {
+                    <table>
+                        { for Quantity::iter().map(|quantity| html! {
+                            <tr>
+                                <td>{ quantity.to_string() }</td>
+                                <td>{ format!("{:.6}", diagnostics.get(quantity)) }</td>
+                            </tr>
+                        }) }
+                    </table>
+                }
+            }
+            None => html! {},
+        }
+    }
+
+    /// Lays the plots out in `width`, at the current density of the screen.
+    fn relayout(&mut self, width: f64) -> ShouldRender {
+        let layout = Layout::new(width);
+        log::trace!("Resizing the plots to {:?}", layout);
+        let changed = layout != self.layout;
+        self.layout = layout;
+        self.replot(changed)
+    }
+
+    /// Plots again at the next render if `changed`, which is returned.
+    fn replot(&mut self, changed: bool) -> ShouldRender {
+        self.replot |= changed;
+        changed
+    }
+
+    /// Sizes the canvases for the current layout.
+    fn resize_canvases(&self) {
+        let (width, height) = self.layout.plot_size();
+        for (canvas_ref, size) in [
+            (&self.current.canvas_ref, (width, height)),
+            (&self.cummulative.canvas_ref, (width, height)),
+            (&self.surface.canvas_ref, (width, height)),
+            (&self.comparison.canvas_ref, (width, height)),
+            (&self.diagnostics.canvas_ref, (width, height / 2)),
+        ] {
+            if let Some(canvas) = canvas_ref.cast::<HtmlCanvasElement>() {
+                self.layout.resize(&canvas, size);
+            }
+        }
+    }
+
+    /// Backend of a sized canvas, if it is displayed.
+    fn backend(&self, canvas_ref: &NodeRef) -> Option<ScaledCanvas> {
+        ScaledCanvas::new(canvas_ref.cast::<HtmlCanvasElement>()?, &self.layout)
+    }
+
+    /// Follows the pointer over `plot`, to show the values under it and to zoom and pan.
+    fn pointer(&mut self, plot: Plot, pointer: Pointer) -> ShouldRender {
+        let coordinates = match plot {
+            Plot::Current => &self.current_coordinates,
+            Plot::Cummulative => &self.cummulative_coordinates,
+            Plot::Surface => return false,
+        };
+        let value = |e: &MouseEvent| {
+            coordinates
+                .as_ref()
+                .and_then(|c| c.value((e.offset_x(), e.offset_y())))
+        };
+        let domain = self.settings.domain;
+        match pointer {
+            Pointer::Move(e) => {
+                let value = value(&e);
+                // Keeps the grabbed x under the pointer
+                let panned = matches!(value, Some((x, _)) if self.zoom.drag(domain, x));
+                self.replot |= panned;
+                let readout = value.map(|(x, y)| match plot {
+                    Plot::Cummulative => {
+                        let time = self.time_at(y);
+                        let u = self.values[time].clamped_sample(x).unwrap_or(f64::NAN);
+                        format!("x = {:.4}, t = {:.4}, u = {:.4}", x, y, u)
+                    }
+                    _ => format!("x = {:.4}, u = {:.4}", x, y),
+                });
+                let changed = readout != self.readout;
+                self.readout = readout;
+                panned || changed
+            }
+            Pointer::Down(e) => {
+                self.zoom.grab(value(&e).map(|(x, _)| x));
+                false
+            }
+            Pointer::Up(e) => {
+                let clicked = self.zoom.release();
+                match value(&e) {
+                    // A click on the heatmap, rather than a drag, jumps to its time
+                    Some((_, t)) if clicked && plot == Plot::Cummulative => {
+                        self.current_time = self.time_at(t);
+                        log::trace!("Jumping to time {}", self.current_time);
+                        self.replot = true;
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            Pointer::Leave => {
+                self.zoom.release();
+                self.readout.take().is_some()
+            }
+            Pointer::Wheel(e) => match value(&e) {
+                Some((x, _)) => {
+                    e.prevent_default();
+                    let factor = if e.delta_y() > 0. {
+                        ZOOM_FACTOR
+                    } else {
+                        1. / ZOOM_FACTOR
+                    };
+                    self.zoom.zoom(domain, x, factor);
+                    self.replot = true;
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+
+    /// Computed time whose column of the heatmap contains `t`.
+    fn time_at(&self, t: f64) -> usize {
+        cummulative::time_at(t, self.settings.kernel.time_step(), self.max_time)
+    }
+
+    fn plot_current<DB>(&self, backend: DB, time: usize) -> anyhow::Result<Coordinates>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
+        let overlay: Vec<_> = self
+            .overlay
+            .times(time)
+            .into_iter()
+            .map(|t| (t, &self.values[t]))
+            .collect();
+        // Limits of all drawn times, so that none is clipped
+        let limits = overlay
+            .iter()
+            .map(|&(t, _)| self.limits.at(self.autoscale, t))
+            .fold(self.limits.at(self.autoscale, time), |a, b| {
+                (a.0.min(b.0), a.1.max(b.1))
+            });
+        self.current.plot(
+            backend,
+            time,
+            self.settings.kernel.time_step(),
+            self.zoom.range(self.settings.domain),
+            limits,
+            self.settings.interpolation,
+            &self.values[time],
+            &overlay,
+            self.reference.as_ref(),
+        )
+    }
+
+    fn plot_cummulative<DB>(&self, backend: DB) -> anyhow::Result<Coordinates>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
+        self.cummulative.plot(
+            backend,
+            self.current_time,
+            self.settings.kernel.time_step(),
+            &self.limits.frames(self.autoscale),
+            &self.heatmap,
+            self.zoom.range,
+            &self.values,
+        )
+    }
+
+    fn plot_surface<DB>(&self, backend: DB) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
+        // A single vertical axis covers the limits of all times
+        let limits = self
+            .limits
+            .frames(self.autoscale)
+            .into_iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |a, b| {
+                (a.0.min(b.0), a.1.max(b.1))
+            });
+        self.surface.plot(
+            backend,
+            self.settings.kernel.time_step(),
+            self.viewpoint,
+            limits,
+            &self.heatmap,
+            &self.values,
+        )
+    }
+
+    /// Offers the evolution of the current plot over all computed times for download.
+    fn export_animation(&self) -> anyhow::Result<()> {
+        let bytes = self.animation.encode_gif(self.values.len(), |time, b| {
+            self.plot_current(b, time).map(drop)
+        })?;
+        download("heat_eq.gif", "image/gif", &bytes)
+    }
+
+    /// Offers a plot for download.
+    fn export_plot(&self, plot: Plot, format: ImageFormat) -> anyhow::Result<()> {
+        let size = self.layout.plot_size();
+        let bytes = match (plot, format) {
+            (Plot::Current, ImageFormat::Png) => {
+                to_png(size, |b| self.plot_current(b, self.current_time).map(drop))?
+            }
+            (Plot::Current, ImageFormat::Svg) => {
+                to_svg(size, |b| self.plot_current(b, self.current_time).map(drop))?
+            }
+            (Plot::Cummulative, ImageFormat::Png) => {
+                to_png(size, |b| self.plot_cummulative(b).map(drop))?
+            }
+            (Plot::Cummulative, ImageFormat::Svg) => {
+                to_svg(size, |b| self.plot_cummulative(b).map(drop))?
+            }
+            (Plot::Surface, ImageFormat::Png) => to_png(size, |b| self.plot_surface(b))?,
+            (Plot::Surface, ImageFormat::Svg) => to_svg(size, |b| self.plot_surface(b))?,
+        };
+        let file_name = format!("heat_eq_{}.{}", plot, format.extension());
+        download(&file_name, format.mime(), &bytes)
+    }
+
+    /// Offers the computed values for download.
+    fn export(&self, format: ExportFormat) -> anyhow::Result<()> {
+        let results = Results::new(&self.values, self.settings.kernel.time_step());
+        let file_name = format!("heat_eq.{}", format.extension());
+        download(&file_name, format.mime(), &results.to_bytes(format)?)
+    }
+
+    /// Looks for values computed before from the current settings.
+    fn restore(&self) {
+        if let Some(key) = self.cache_key.clone() {
+            let link = self.link.clone();
+            let interpolation = self.settings.interpolation;
+            wasm_bindgen_futures::spawn_local(async move {
+                match cache::load(&key, interpolation).await {
+                    Ok(Some(values)) => link.send_message(Msg::Restored(key, values)),
+                    Ok(None) => log::trace!("There are no cached values for {}", key.hash),
+                    Err(e) => log::warn!("Could not read the cache: {}", e),
+                }
+            });
+        }
+    }
+
+    /// Appends the values not yet in the cache, one save at a time.
+    fn save(&mut self) {
+        if self.saving || self.values.len() <= self.saved {
+            return;
+        }
+        if let Some(key) = self.cache_key.clone() {
+            self.saving = true;
+            let link = self.link.clone();
+            let first = self.saved;
+            let slices = self.values[first..].to_vec();
+            wasm_bindgen_futures::spawn_local(async move {
+                let saved = match cache::save(&key, first, &slices).await {
+                    Ok(()) => Some(first + slices.len()),
+                    Err(e) => {
+                        log::warn!("Could not cache the computed values: {}", e);
+                        None
+                    }
+                };
+                link.send_message(Msg::Saved(key, saved));
+            });
+        }
+    }
+
     /// Computes the next time point and saves the result.
     fn compute_next(&mut self) -> &mut Self {
         let kernel = &self.settings.kernel;
         log::trace!("Retrieving current spline");
         let last_spline = self.values.last().unwrap();
 
-        let border_conditions = &self.settings.border_conditions;
+        let _border_conditions = &self.settings.border_conditions;
 
-        let mut new_keys = vec![];
+        let mut new_points = vec![];
         log::trace!("Computing new spline");
         // match border_conditions {
 
@@ -182,13 +809,52 @@ impl Analysis {
                 quadrature::integrate(integrand, effective_interval.0, effective_interval.1, 1e-7)
                     .integral;
 
-            new_keys.push(splines::Key::new(
-                *x,
-                new_value,
-                splines::Interpolation::Cosine,
-            ));
+            new_points.push((*x, new_value));
         }
-        self.values.push(Spline::from_vec(new_keys));
+        let spline = self.settings.interpolation.spline(new_points);
+        self.limits.push(&spline);
+        self.values.push(spline);
         self
     }
 }
+
+/// Parses the option chosen in a `<select>`.
+fn select<T>(data: ChangeData) -> Option<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    match data {
+        ChangeData::Select(select_element) => {
+            let value = select_element.value();
+            value
+                .parse()
+                .map_err(|e| log::error!("Unknown option {}: {}", value, e))
+                .ok()
+        }
+        data => {
+            log::error!("Tried to select {:?}", data);
+            None
+        }
+    }
+}
+
+/// Five points on [-2, 2] at three times, the value at the `i`-th point and `j`-th time being `i * j`.
+#[cfg(test)]
+pub fn test_splines() -> Vec<Spline<f64, f64>> {
+    (0..3)
+        .map(|j| {
+            Spline::from_vec(
+                (0..5)
+                    .map(|i| {
+                        Key::new(
+                            i as f64 - 2.,
+                            (i * j) as f64,
+                            splines::Interpolation::Linear,
+                        )
+                    })
+                    .collect(),
+            )
+        })
+        .collect()
+}
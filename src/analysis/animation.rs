@@ -0,0 +1,140 @@
+//! Export of the evolution as an animation
+
+use core::convert::TryFrom;
+use yew::prelude::*;
+
+use super::export::{rasterize, Raster};
+
+/// Speed of the color quantization of frames, from 1 (best quality) to 30 (fastest).
+const QUANTIZATION_SPEED: i32 = 10;
+
+#[derive(Debug)]
+pub enum SetAnimation {
+    Delay(ChangeData),
+    Width(ChangeData),
+    Height(ChangeData),
+}
+
+/// Options of the exported animation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnimationOptions {
+    /// Time between frames, in milliseconds.
+    pub delay: u32,
+    pub size: (u16, u16),
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        Self {
+            delay: 200,
+            size: (360, 360),
+        }
+    }
+}
+
+impl AnimationOptions {
+    pub fn update(&mut self, set: SetAnimation) -> ShouldRender {
+        match set {
+            SetAnimation::Delay(ChangeData::Value(x)) => {
+                log::trace!("Trying to change animation delay to {}", x);
+                match x.parse() {
+                    Ok(delay) => {
+                        self.delay = delay;
+                        true
+                    }
+                    Err(_) => {
+                        log::warn!("Invalid animation delay: {}", x);
+                        false
+                    }
+                }
+            }
+            SetAnimation::Width(ChangeData::Value(x)) => {
+                log::trace!("Trying to change animation width to {}", x);
+                match x.parse() {
+                    Ok(width) if width > 0 => {
+                        self.size.0 = width;
+                        true
+                    }
+                    _ => {
+                        log::warn!("Invalid animation width: {}", x);
+                        false
+                    }
+                }
+            }
+            SetAnimation::Height(ChangeData::Value(x)) => {
+                log::trace!("Trying to change animation height to {}", x);
+                match x.parse() {
+                    Ok(height) if height > 0 => {
+                        self.size.1 = height;
+                        true
+                    }
+                    _ => {
+                        log::warn!("Invalid animation height: {}", x);
+                        false
+                    }
+                }
+            }
+            set => {
+                log::error!("Tried to change animation options with {:?}", set);
+                false
+            }
+        }
+    }
+
+    /// Encodes an animated GIF whose frames are drawn with `plot`.
+    pub fn encode_gif<F>(&self, frames: usize, mut plot: F) -> anyhow::Result<Vec<u8>>
+    where
+        F: FnMut(usize, Raster) -> anyhow::Result<()>,
+    {
+        let (width, height) = self.size;
+        let mut bytes = vec![];
+        {
+            let mut encoder = gif::Encoder::new(&mut bytes, width, height, &[])?;
+            encoder.set_repeat(gif::Repeat::Infinite)?;
+            for i in 0..frames {
+                let mut pixels =
+                    rasterize((width.into(), height.into()), |backend| plot(i, backend))?;
+                let mut frame =
+                    gif::Frame::from_rgba_speed(width, height, &mut pixels, QUANTIZATION_SPEED);
+                // GIF delays are in hundredths of a second
+                frame.delay = u16::try_from(self.delay / 10).unwrap_or(u16::MAX);
+                encoder.write_frame(&frame)?;
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plotters::prelude::*;
+
+    #[test]
+    fn gif() -> anyhow::Result<()> {
+        let options = AnimationOptions {
+            delay: 100,
+            size: (4, 3),
+        };
+        let bytes = options.encode_gif(2, |i, backend| {
+            let color = if i == 0 { &RED } else { &BLUE };
+            let root = backend.into_drawing_area();
+            root.fill(color)?;
+            root.titled(&i.to_string(), ("sans-serif", 2))?;
+            Ok(())
+        })?;
+        assert_eq!(&bytes[0..6], b"GIF89a");
+
+        let mut decoder = gif::DecodeOptions::new();
+        decoder.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = decoder.read_info(bytes.as_slice())?;
+        let mut frames = 0;
+        while let Some(frame) = decoder.read_next_frame()? {
+            assert_eq!((frame.width, frame.height), (4, 3));
+            assert_eq!(frame.delay, 10);
+            frames += 1;
+        }
+        assert_eq!(frames, 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,447 @@
+//! Cache of computed time slices
+//!
+//! Computed slices are kept in IndexedDB, keyed by a hash of the settings, so that reloading
+//! the page or restarting from the same settings does not compute them again. Each slice is
+//! stored on its own, as the exact bits of its points, so that new slices are appended without
+//! writing the previous ones again and that non-finite values are kept. An index with the
+//! size, number of slices and last use of each entry is kept alongside them. When a new entry
+//! does not fit in the budget, which depends on the storage quota of the browser, the least
+//! recently used entries are evicted.
+
+use core::convert::TryInto;
+use js_sys::{Date, Promise, Reflect};
+use serde::{Deserialize, Serialize};
+use splines::Spline;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Event, IdbDatabase, IdbObjectStore, IdbRequest, IdbTransactionMode};
+
+use crate::configuration::settings::{InterpolationKind, Settings};
+
+const DATABASE: &str = "yew.heat_eq.cache";
+/// Version of the database, whose stores are emptied by upgrades as their format changed.
+const DATABASE_VERSION: u32 = 2;
+/// Object store of the headers of the entries, by hash, and of their slices, by hash and time.
+const ENTRIES: &str = "entries";
+/// Object store of the index, under `INDEX_KEY`.
+const META: &str = "meta";
+const INDEX_KEY: &str = "index";
+/// Largest size of the cache, in bytes, whatever the quota.
+const MAX_BYTES: usize = 50 * 1024 * 1024;
+/// Fraction of the storage quota the cache may use.
+const QUOTA_FRACTION: f64 = 0.1;
+
+pub type Values = Vec<Spline<f64, f64>>;
+
+thread_local! {
+    /// Database opened by the first access to the cache, and kept open for the next ones.
+    static OPENED: RefCell<Option<IdbDatabase>> = const { RefCell::new(None) };
+}
+
+/// Identifies the values computed from some settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheKey {
+    pub hash: String,
+    /// Settings that determine the values, as RON, to tell apart colliding hashes.
+    settings: String,
+}
+
+impl CacheKey {
+    pub fn new(settings: &Settings) -> anyhow::Result<Self> {
+        let settings = ron::ser::to_string(settings)?;
+        Ok(CacheKey {
+            hash: format!("{:016x}", fnv1a(settings.as_bytes())),
+            settings,
+        })
+    }
+}
+
+/// FNV-1a hash, which is stable across platforms and versions.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// First record of an entry, followed by its slices.
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    settings: String,
+    /// Number of stored slices.
+    slices: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct EntryInfo {
+    bytes: usize,
+    slices: usize,
+    /// Milliseconds since the epoch.
+    last_used: f64,
+}
+
+/// Key of the `time`-th slice of the entry `hash`.
+fn slice_key(hash: &str, time: usize) -> String {
+    format!("{}/{}", hash, time)
+}
+
+/// Points of a slice, as base64 of the bits of each coordinate.
+fn encode(spline: &Spline<f64, f64>) -> String {
+    let bytes: Vec<u8> = spline
+        .keys()
+        .iter()
+        .flat_map(|key| [key.t, key.value])
+        .flat_map(f64::to_le_bytes)
+        .collect();
+    base64::encode(bytes)
+}
+
+/// Slice of the points encoded by [`encode`].
+fn decode(encoded: &str, interpolation: InterpolationKind) -> anyhow::Result<Spline<f64, f64>> {
+    let bytes = base64::decode(encoded)?;
+    if bytes.len() % 16 != 0 {
+        anyhow::bail!("A cached slice has {} bytes", bytes.len());
+    }
+    let value = |bytes: &[u8]| f64::from_le_bytes(bytes.try_into().unwrap());
+    Ok(interpolation.spline(
+        bytes
+            .chunks(16)
+            .map(|point| (value(&point[..8]), value(&point[8..]))),
+    ))
+}
+
+/// Size and last use of the cached entries, by hash.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct Index {
+    entries: BTreeMap<String, EntryInfo>,
+}
+
+impl Index {
+    fn total(&self) -> usize {
+        self.entries.values().map(|info| info.bytes).sum()
+    }
+
+    fn touch(&mut self, hash: &str, bytes: usize, slices: usize, now: f64) {
+        self.entries.insert(
+            hash.to_string(),
+            EntryInfo {
+                bytes,
+                slices,
+                last_used: now,
+            },
+        );
+    }
+
+    /// Removes the least recently used entries until `bytes` more fit in `budget`.
+    ///
+    /// Returns the removed entries.
+    fn evict(&mut self, bytes: usize, budget: usize) -> anyhow::Result<Vec<(String, EntryInfo)>> {
+        if bytes > budget {
+            anyhow::bail!(
+                "The values take {} bytes, more than the {} bytes of the cache",
+                bytes,
+                budget
+            );
+        }
+        let mut evicted = vec![];
+        while self.total() + bytes > budget {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by(|(_, a), (_, b)| a.last_used.total_cmp(&b.last_used))
+                .map(|(hash, _)| hash.clone())
+                .unwrap();
+            let info = self.entries.remove(&oldest).unwrap();
+            evicted.push((oldest, info));
+        }
+        Ok(evicted)
+    }
+}
+
+/// Reads the values cached for `key`, if any, as splines of `interpolation`.
+pub async fn load(
+    key: &CacheKey,
+    interpolation: InterpolationKind,
+) -> anyhow::Result<Option<Values>> {
+    let db = open().await?;
+    let header: Header = match get(&db, ENTRIES, &key.hash).await? {
+        Some(json) => serde_json::from_str(&json)?,
+        None => return Ok(None),
+    };
+    if header.settings != key.settings {
+        log::warn!(
+            "Ignoring cached values of other settings with hash {}",
+            key.hash
+        );
+        return Ok(None);
+    }
+    let mut values = Vec::with_capacity(header.slices);
+    for time in 0..header.slices {
+        match get(&db, ENTRIES, &slice_key(&key.hash, time)).await? {
+            Some(encoded) => values.push(decode(&encoded, interpolation)?),
+            None => anyhow::bail!("The cached slice {} of {} is missing", time, key.hash),
+        }
+    }
+
+    let mut index = read_index(&db).await?;
+    if let Some(info) = index.entries.get(&key.hash).copied() {
+        index.touch(&key.hash, info.bytes, info.slices, Date::now());
+        write_index(&db, &index).await?;
+    }
+    Ok(Some(values))
+}
+
+/// Caches `slices` for `key`, starting at time `first`, evicting other entries if needed.
+///
+/// # Errors
+///
+/// If the cache does not hold the `first` previous slices, in which case they should be saved
+/// again from the start.
+pub async fn save(key: &CacheKey, first: usize, slices: &[Spline<f64, f64>]) -> anyhow::Result<()> {
+    let encoded: Vec<String> = slices.iter().map(encode).collect();
+    let header = serde_json::to_string(&Header {
+        settings: key.settings.clone(),
+        slices: first + slices.len(),
+    })?;
+    let budget = budget().await;
+    let db = open().await?;
+
+    let mut index = read_index(&db).await?;
+    let previous = index.entries.remove(&key.hash);
+    let kept = match previous {
+        _ if first == 0 => 0,
+        Some(info) if info.slices == first => info.bytes,
+        _ => anyhow::bail!("The cache does not hold the {} first slices", first),
+    };
+    let bytes = kept + header.len() + encoded.iter().map(String::len).sum::<usize>();
+    for (hash, info) in index.evict(bytes, budget)? {
+        log::debug!("Evicting cached values {}", hash);
+        delete_entry(&db, &hash, info.slices).await?;
+    }
+    if let (0, Some(info)) = (first, previous) {
+        delete_entry(&db, &key.hash, info.slices).await?;
+    }
+    let written = async {
+        for (time, slice) in (first..).zip(&encoded) {
+            put(&db, ENTRIES, &slice_key(&key.hash, time), slice).await?;
+        }
+        put(&db, ENTRIES, &key.hash, &header).await
+    }
+    .await;
+    if let Err(e) = written {
+        // The quota is shared with other data, so the estimate might have been too optimistic
+        log::warn!("Could not cache values, clearing the cache: {}", e);
+        clear(&db, ENTRIES).await?;
+        write_index(&db, &Index::default()).await?;
+        return Err(e);
+    }
+    index.touch(&key.hash, bytes, first + slices.len(), Date::now());
+    write_index(&db, &index).await
+}
+
+/// Size the cache may take, in bytes.
+async fn budget() -> usize {
+    match quota().await {
+        Ok(quota) => MAX_BYTES.min((quota * QUOTA_FRACTION) as usize),
+        Err(e) => {
+            log::debug!("Could not estimate the storage quota: {}", e);
+            MAX_BYTES
+        }
+    }
+}
+
+async fn quota() -> anyhow::Result<f64> {
+    let promise = web_sys::window()
+        .unwrap()
+        .navigator()
+        .storage()
+        .estimate()
+        .map_err(js_error)?;
+    let estimate = JsFuture::from(promise).await.map_err(js_error)?;
+    Reflect::get(&estimate, &JsValue::from_str("quota"))
+        .map_err(js_error)?
+        .as_f64()
+        .ok_or_else(|| anyhow::anyhow!("The storage quota is unknown"))
+}
+
+/// Opens the database, or returns it if it is already open.
+async fn open() -> anyhow::Result<IdbDatabase> {
+    if let Some(db) = OPENED.with(|opened| opened.borrow().clone()) {
+        return Ok(db);
+    }
+    let factory = web_sys::window()
+        .unwrap()
+        .indexed_db()
+        .map_err(js_error)?
+        .ok_or_else(|| anyhow::anyhow!("IndexedDB is not available"))?;
+    let request = factory
+        .open_with_u32(DATABASE, DATABASE_VERSION)
+        .map_err(js_error)?;
+    let upgrade = Closure::wrap(Box::new(|event: Event| {
+        if let Err(e) = create_stores(&event) {
+            log::error!("Could not create the cache: {:?}", e);
+        }
+    }) as Box<dyn FnMut(Event)>);
+    request.set_onupgradeneeded(Some(upgrade.as_ref().unchecked_ref()));
+    let db: IdbDatabase = wait(&request).await?.unchecked_into();
+    // Another page opening a newer version waits for this one to close it
+    let version_change = Closure::wrap(Box::new(|event: Event| {
+        if let Some(db) = event.target() {
+            db.unchecked_into::<IdbDatabase>().close();
+        }
+        OPENED.with(|opened| opened.borrow_mut().take());
+    }) as Box<dyn FnMut(Event)>);
+    db.set_onversionchange(Some(version_change.as_ref().unchecked_ref()));
+    version_change.forget();
+    OPENED.with(|opened| *opened.borrow_mut() = Some(db.clone()));
+    Ok(db)
+}
+
+/// Creates the stores, dropping those of older versions.
+fn create_stores(event: &Event) -> Result<(), JsValue> {
+    let db: IdbDatabase = event
+        .target()
+        .ok_or_else(|| JsValue::from_str("The upgrade has no request"))?
+        .unchecked_into::<IdbRequest>()
+        .result()?
+        .unchecked_into();
+    for name in &[ENTRIES, META] {
+        if db.object_store_names().contains(name) {
+            db.delete_object_store(name)?;
+        }
+        db.create_object_store(name)?;
+    }
+    Ok(())
+}
+
+async fn read_index(db: &IdbDatabase) -> anyhow::Result<Index> {
+    match get(db, META, INDEX_KEY).await? {
+        Some(json) => Ok(serde_json::from_str(&json)?),
+        None => Ok(Index::default()),
+    }
+}
+
+async fn write_index(db: &IdbDatabase, index: &Index) -> anyhow::Result<()> {
+    put(db, META, INDEX_KEY, &serde_json::to_string(index)?).await
+}
+
+fn store(db: &IdbDatabase, name: &str, mode: IdbTransactionMode) -> anyhow::Result<IdbObjectStore> {
+    db.transaction_with_str_and_mode(name, mode)
+        .and_then(|transaction| transaction.object_store(name))
+        .map_err(js_error)
+}
+
+async fn get(db: &IdbDatabase, name: &str, key: &str) -> anyhow::Result<Option<String>> {
+    let request = store(db, name, IdbTransactionMode::Readonly)?
+        .get(&JsValue::from_str(key))
+        .map_err(js_error)?;
+    Ok(wait(&request).await?.as_string())
+}
+
+async fn put(db: &IdbDatabase, name: &str, key: &str, value: &str) -> anyhow::Result<()> {
+    let request = store(db, name, IdbTransactionMode::Readwrite)?
+        .put_with_key(&JsValue::from_str(value), &JsValue::from_str(key))
+        .map_err(js_error)?;
+    wait(&request).await?;
+    Ok(())
+}
+
+/// Deletes the header and the `slices` slices of the entry `hash`.
+async fn delete_entry(db: &IdbDatabase, hash: &str, slices: usize) -> anyhow::Result<()> {
+    delete(db, ENTRIES, hash).await?;
+    for time in 0..slices {
+        delete(db, ENTRIES, &slice_key(hash, time)).await?;
+    }
+    Ok(())
+}
+
+async fn clear(db: &IdbDatabase, name: &str) -> anyhow::Result<()> {
+    let request = store(db, name, IdbTransactionMode::Readwrite)?
+        .clear()
+        .map_err(js_error)?;
+    wait(&request).await?;
+    Ok(())
+}
+
+async fn delete(db: &IdbDatabase, name: &str, key: &str) -> anyhow::Result<()> {
+    let request = store(db, name, IdbTransactionMode::Readwrite)?
+        .delete(&JsValue::from_str(key))
+        .map_err(js_error)?;
+    wait(&request).await?;
+    Ok(())
+}
+
+/// Waits for the result of an IndexedDB request.
+async fn wait(request: &IdbRequest) -> anyhow::Result<JsValue> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    if JsFuture::from(promise).await.is_err() {
+        let message = match request.error() {
+            Ok(Some(e)) => format!("{}: {}", e.name(), e.message()),
+            _ => "Unknown error".to_string(),
+        };
+        anyhow::bail!("IndexedDB request failed. {}", message);
+    }
+    request.result().map_err(js_error)
+}
+
+fn js_error(e: JsValue) -> anyhow::Error {
+    anyhow::anyhow!("{:?}", e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key() -> anyhow::Result<()> {
+        let settings = Settings::default();
+        let key = CacheKey::new(&settings)?;
+        assert_eq!(key.hash.len(), 16);
+
+        assert_eq!(CacheKey::new(&Settings::default())?, key);
+        let other = Settings {
+            quality: 42,
+            ..Default::default()
+        };
+        assert_ne!(CacheKey::new(&other)?.hash, key.hash);
+        Ok(())
+    }
+
+    #[test]
+    fn evict() -> anyhow::Result<()> {
+        let mut index = Index::default();
+        index.touch("a", 10, 1, 2.);
+        index.touch("b", 10, 2, 1.);
+        index.touch("c", 10, 3, 3.);
+        assert!(index.evict(10, 40)?.is_empty());
+        let evicted = index.evict(10, 30)?;
+        assert_eq!(evicted.len(), 1);
+        assert_eq!((evicted[0].0.as_str(), evicted[0].1.slices), ("b", 2));
+        assert_eq!(index.evict(15, 30)?[0].0, "a");
+        assert_eq!(index.total(), 10);
+        assert!(index.evict(31, 30).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn encoding() -> anyhow::Result<()> {
+        let interpolation = InterpolationKind::Linear;
+        let spline =
+            interpolation.spline(vec![(-1., 0.1), (0., f64::NAN), (1., f64::NEG_INFINITY)]);
+        let decoded = decode(&encode(&spline), interpolation)?;
+        let bits = |spline: &Spline<f64, f64>| -> Vec<(u64, u64)> {
+            spline
+                .keys()
+                .iter()
+                .map(|key| (key.t.to_bits(), key.value.to_bits()))
+                .collect()
+        };
+        assert_eq!(bits(&decoded), bits(&spline));
+        assert!(decode("AAAA", interpolation).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+use plotters::prelude::*;
+use splines::Spline;
+use yew::prelude::*;
+
+use super::reference::{Norms, Reference};
+
+/// Difference with a reference solution
+#[derive(Debug)]
+pub struct Comparison {
+    pub canvas_ref: NodeRef,
+}
+
+impl Comparison {
+    pub fn new(canvas_ref: NodeRef) -> Self {
+        Self { canvas_ref }
+    }
+
+    /// Plots the pointwise error at `current_time` above the norms of the error over time.
+    pub fn plot<DB>(
+        &self,
+        backend: DB,
+        current_time: usize,
+        time_step: f64,
+        reference: &Reference,
+        splines: &[Spline<f64, f64>],
+    ) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
+        let errors = errors(reference, time_step, splines);
+        let norms: Vec<(f64, Norms)> = errors
+            .iter()
+            .enumerate()
+            .map(|(time, errors)| (time as f64 * time_step, Norms::new(errors)))
+            .collect();
+
+        let root = backend.into_drawing_area();
+        root.fill(&WHITE)?;
+        let (pointwise_area, norms_area) = root.split_vertically(root.dim_in_pixel().1 / 2);
+
+        // Pointwise error
+        let current = &errors[current_time];
+        let (x_min, x_max) = bounds(current.iter().map(|&(x, _)| x));
+        let (e_min, e_max) = bounds(current.iter().map(|&(_, e)| e));
+        let mut chart = ChartBuilder::on(&pointwise_area)
+            .caption(
+                format!("Error at t = {:.3}", current_time as f64 * time_step),
+                ("Arial", 20),
+            )
+            .margin(5)
+            .set_label_area_size(LabelAreaPosition::Bottom, 30)
+            .set_label_area_size(LabelAreaPosition::Left, 50)
+            .build_cartesian_2d(x_min..x_max, e_min..e_max)?;
+        chart.configure_mesh().x_desc("x").draw()?;
+        chart.draw_series(LineSeries::new(
+            current.iter().copied().filter(|(_, e)| e.is_finite()),
+            &BLACK,
+        ))?;
+
+        // Norms over time
+        let (t_min, t_max) = bounds(norms.iter().map(|&(t, _)| t));
+        let (_, n_max) = bounds(
+            norms
+                .iter()
+                .flat_map(|(_, norms)| vec![0., norms.l2, norms.linf]),
+        );
+        let mut chart = ChartBuilder::on(&norms_area)
+            .caption("Error norms", ("Arial", 20))
+            .margin(5)
+            .set_label_area_size(LabelAreaPosition::Bottom, 30)
+            .set_label_area_size(LabelAreaPosition::Left, 50)
+            .build_cartesian_2d(t_min..t_max, 0f64..n_max)?;
+        chart.configure_mesh().x_desc("t").draw()?;
+        chart
+            .draw_series(LineSeries::new(
+                norms.iter().map(|&(t, norms)| (t, norms.l2)),
+                &BLUE,
+            ))?
+            .label("L2")
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE));
+        chart
+            .draw_series(LineSeries::new(
+                norms.iter().map(|&(t, norms)| (t, norms.linf)),
+                &RED,
+            ))?
+            .label("L∞")
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+
+        root.present()?;
+
+        Ok(())
+    }
+}
+
+/// Pointwise errors at each time.
+fn errors(
+    reference: &Reference,
+    time_step: f64,
+    splines: &[Spline<f64, f64>],
+) -> Vec<Vec<(f64, f64)>> {
+    splines
+        .iter()
+        .enumerate()
+        .map(|(time, spline)| reference.errors(spline, time as f64 * time_step))
+        .collect()
+}
+
+/// Smallest and largest finite values, widened if they are equal.
+fn bounds<I: IntoIterator<Item = f64>>(values: I) -> (f64, f64) {
+    let (min, max) = values
+        .into_iter()
+        .filter(|value| value.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+            (min.min(value), max.max(value))
+        });
+    if min > max {
+        (-1., 1.)
+    } else if min == max {
+        (min - 0.5, max + 0.5)
+    } else {
+        (min, max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::test_splines;
+
+    #[test]
+    fn errors() -> anyhow::Result<()> {
+        // The values are (x + 2) t, so the error is 2 t everywhere
+        let reference: Reference = "{x} * {t}".parse()?;
+        let splines = test_splines();
+        let errors = super::errors(&reference, 1., &splines);
+        assert_eq!(errors.len(), 3);
+        for (time, errors) in errors.iter().enumerate() {
+            let expected: Vec<(f64, f64)> =
+                (-2..=2).map(|x| (x as f64, 2. * time as f64)).collect();
+            assert_eq!(errors, &expected);
+        }
+
+        let mut svg = String::new();
+        Comparison::new(NodeRef::default()).plot(
+            SVGBackend::with_string(&mut svg, (360, 360)),
+            1,
+            1.,
+            &reference,
+            &splines,
+        )?;
+        assert!(svg.contains("Error at t = 1.000"));
+        // The L2 norm of the last error, 4 on an interval of length 4, is the top of the norms
+        assert!(svg.contains("\n8.0\n"));
+        assert!(!svg.contains("\n9.0\n"));
+        Ok(())
+    }
+}
@@ -1,10 +1,17 @@
 use itertools::Itertools;
 use nalgebra::DMatrix;
 use plotters::prelude::*;
-use plotters_canvas::CanvasBackend;
 use splines::Spline;
 use yew::prelude::*;
 
+use super::heatmap::HeatmapOptions;
+use super::zoom::Coordinates;
+
+/// Width of the colorbar, including its labels.
+const COLORBAR_WIDTH: u32 = 70;
+/// Number of colors of the colorbar.
+const COLORBAR_STEPS: usize = 100;
+
 /// Function at a current time
 #[derive(Debug)]
 pub struct Cummulative {
@@ -16,84 +23,225 @@ impl Cummulative {
         Self { canvas_ref }
     }
 
-    /// Assumes that all Spline has the same uniform grid
-    pub fn plot(
+    /// Plots `splines`, a spline per computed time, as a heatmap over space and time.
+    /// `limit_values` are the limits of the colors at each time.
+    /// Only the cells within `x_range` are drawn, if it is given.
+    ///
+    /// Returns the coordinates of the heatmap, to map the pointer back to (x, t).
+    #[allow(clippy::too_many_arguments)]
+    pub fn plot<DB>(
         &self,
-        backend: CanvasBackend,
+        backend: DB,
         current_time: usize,
-        limit_values: (f64, f64),
-        splines: &Vec<Spline<f64, f64>>,
-    ) -> anyhow::Result<()> {
+        time_step: f64,
+        limit_values: &[(f64, f64)],
+        options: &HeatmapOptions,
+        x_range: Option<(f64, f64)>,
+        splines: &[Spline<f64, f64>],
+    ) -> anyhow::Result<Coordinates>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
         // Pre-computations
-        let (mut min, mut max) = limit_values;
-        log::trace!("min/max values of the plot: ({}, {})", min, max,);
-        if !min.is_finite() {
-            log::error!("min value is not real!");
-            min = -1.;
-            log::warn!("min value changed to {}", min);
-        }
-        if !max.is_finite() {
-            log::error!("max value is not real!");
-            max = 1.;
-            log::warn!("max value changed to {}", max);
-        }
+        let ranges: Vec<(f64, f64)> = limit_values
+            .iter()
+            .map(|&limits| options.range(limits))
+            .collect();
+        log::trace!(
+            "min/max values of the plot at the current time: {:?}",
+            ranges[current_time]
+        );
 
-        let rows = splines[0].keys().len();
-        let cols = splines.len();
-        log::trace!("Plotting a {}x{} matrix", rows, cols);
-        let mut matrix = DMatrix::from_element(rows, cols, 0.0); // , 0.0);
-        for i in 0..rows {
-            for j in 0..cols {
-                matrix[(i, j)] = splines[j].get(i).unwrap().value;
-            }
-        }
+        let space: Vec<f64> = splines[0].keys().iter().map(|key| key.t).collect();
+        let matrix = matrix(splines);
+        log::trace!("Plotting a {}x{} matrix", matrix.nrows(), matrix.ncols());
 
-        plot_matrix(backend, matrix, limit_values, current_time)?;
+        plot_matrix(
+            backend,
+            matrix,
+            &space,
+            time_step,
+            options,
+            &ranges,
+            current_time,
+            x_range,
+        )
+    }
+}
 
-        Ok(())
+/// Values of the splines, with space as rows and time as columns.
+///
+/// Assumes that all splines share the same uniform grid.
+pub fn matrix(splines: &[Spline<f64, f64>]) -> DMatrix<f64> {
+    let rows = splines[0].keys().len();
+    let cols = splines.len();
+    let mut matrix = DMatrix::from_element(rows, cols, 0.0);
+    for i in 0..rows {
+        for j in 0..cols {
+            matrix[(i, j)] = splines[j].get(i).unwrap().value;
+        }
     }
+    matrix
+}
+
+/// Column of the matrix whose cell contains `t`, among the first `max_time` + 1.
+///
+/// Each column spans half a time step on each side of its time.
+pub fn time_at(t: f64, time_step: f64, max_time: usize) -> usize {
+    ((t / time_step).round().max(0.) as usize).min(max_time)
 }
 
 /// Plot a matrix when a index highlighted by using a different color scheme.
 ///
-/// The value of the matrix corresponds to the color value, which are rescaled by `limit_values`.
-fn plot_matrix(
-    backend: CanvasBackend,
+/// Rows are drawn at the points of `space` and columns at multiples of `time_step`.
+/// The value of the matrix corresponds to the color value, which are rescaled from the range of
+/// their column according to `options`. The colorbar on the right shows the highlighted column.
+/// Cells are clipped to `x_range`, if it is given.
+#[allow(clippy::too_many_arguments)]
+fn plot_matrix<DB>(
+    backend: DB,
     matrix: DMatrix<f64>,
-    limit_values: (f64, f64),
+    space: &[f64],
+    time_step: f64,
+    options: &HeatmapOptions,
+    ranges: &[(f64, f64)],
     highlighted_index: usize,
-) -> anyhow::Result<()> {
-    let gradient = colorous::VIRIDIS;
-    let highlight_gradient = colorous::MAGMA;
+    x_range: Option<(f64, f64)>,
+) -> anyhow::Result<Coordinates>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let gradient = options.colormap.gradient();
+    let highlight_gradient = options.highlight.gradient();
 
-    let values_recip = (limit_values.1 - limit_values.0).recip();
-    let value_scaling = |v| (v - limit_values.0) * values_recip;
+    let color = |gradient: colorous::Gradient, v: f64, range: (f64, f64)| {
+        let color = gradient.eval_continuous(options.position(v, range));
+        RGBColor(color.r, color.g, color.b)
+    };
 
     let (rows, columns) = matrix.shape();
+    // Each value is drawn as a cell centered at its point
+    let half_dx = if rows > 1 {
+        (space[rows - 1] - space[0]) / (rows - 1) as f64 / 2.
+    } else {
+        0.5
+    };
+    let half_dt = time_step / 2.;
+    let x_range = x_range.unwrap_or((space[0] - half_dx, space[rows - 1] + half_dx));
 
     let root = backend.into_drawing_area();
     root.fill(&WHITE)?;
+    let (width, _) = root.dim_in_pixel();
+    let (main, colorbar) = root.split_horizontally(width.saturating_sub(COLORBAR_WIDTH));
 
-    let mut chart = ChartBuilder::on(&root).build_cartesian_2d(0..rows, 0..columns)?;
-    chart.configure_mesh().draw()?;
+    let mut chart = ChartBuilder::on(&main)
+        .caption("Evolution u(x, t)", ("Arial", 20))
+        .margin(5)
+        .set_label_area_size(LabelAreaPosition::Bottom, 35)
+        .set_label_area_size(LabelAreaPosition::Left, 45)
+        .build_cartesian_2d(
+            x_range.0..x_range.1,
+            -half_dt..(columns as f64 * time_step - half_dt),
+        )?;
 
     chart.draw_series(
         (0..rows)
+            .filter(|&i| space[i] + half_dx > x_range.0 && space[i] - half_dx < x_range.1)
             .cartesian_product(0..columns)
-            .map(|(i, j)| (i, j, value_scaling(matrix[(i, j)])))
-            .map(|(i, j, v)| {
-                Rectangle::new([(i, j), (i + 1, j + 1)], {
-                    let color = if j == highlighted_index {
-                        highlight_gradient.eval_continuous(v)
-                    } else {
-                        gradient.eval_continuous(v)
-                    };
-                    RGBColor(color.r, color.g, color.b).filled()
-                })
+            .map(|(i, j)| {
+                let (x, t) = (space[i], j as f64 * time_step);
+                let gradient = if j == highlighted_index {
+                    highlight_gradient
+                } else {
+                    gradient
+                };
+                Rectangle::new(
+                    [
+                        ((x - half_dx).max(x_range.0), t - half_dt),
+                        ((x + half_dx).min(x_range.1), t + half_dt),
+                    ],
+                    color(gradient, matrix[(i, j)], ranges[j]).filled(),
+                )
             }),
     )?;
+    chart
+        .configure_mesh()
+        .disable_mesh()
+        .x_desc("x")
+        .y_desc("t")
+        .draw()?;
+    let coordinates = Coordinates::new(chart.as_coord_spec());
+
+    // Colorbar
+    let range = ranges[highlighted_index];
+    let mut bar = ChartBuilder::on(&colorbar)
+        .margin_top(35)
+        .margin_bottom(40)
+        .margin_right(5)
+        .set_label_area_size(LabelAreaPosition::Right, 45)
+        .build_cartesian_2d(0f64..1f64, range.0..range.1)?;
+    let step = (range.1 - range.0) / COLORBAR_STEPS as f64;
+    bar.draw_series((0..COLORBAR_STEPS).map(|k| {
+        let v = range.0 + k as f64 * step;
+        Rectangle::new(
+            [(0., v), (1., v + step)],
+            color(gradient, v + step / 2., range).filled(),
+        )
+    }))?;
+    bar.configure_mesh()
+        .disable_mesh()
+        .disable_x_axis()
+        .y_desc("u")
+        .draw()?;
 
     root.present()?;
 
-    Ok(())
+    Ok(coordinates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::test_splines;
+
+    fn plot(x_range: Option<(f64, f64)>) -> anyhow::Result<String> {
+        let mut svg = String::new();
+        Cummulative::new(NodeRef::default()).plot(
+            SVGBackend::with_string(&mut svg, (360, 360)),
+            1,
+            0.5,
+            &[(0., 2.), (0., 4.), (0., 8.)],
+            &HeatmapOptions::default(),
+            x_range,
+            &test_splines(),
+        )?;
+        Ok(svg)
+    }
+
+    #[test]
+    fn labels() -> anyhow::Result<()> {
+        let svg = plot(None)?;
+        assert!(svg.contains("Evolution u(x, t)"));
+        // The background, a cell per value and the colorbar
+        assert_eq!(svg.matches("<rect").count(), 1 + 5 * 3 + COLORBAR_STEPS);
+        // The colorbar follows the limits of the current time
+        assert!(svg.contains("\n4.0\n"));
+        assert!(!svg.contains("\n8.0\n"));
+
+        let svg = plot(Some((-1., 1.)))?;
+        assert_eq!(svg.matches("<rect").count(), 1 + 3 * 3 + COLORBAR_STEPS);
+        Ok(())
+    }
+
+    #[test]
+    fn time_at() {
+        assert_eq!(super::time_at(0.74, 0.5, 2), 1);
+        assert_eq!(super::time_at(0.76, 0.5, 2), 2);
+        // Past the last computed time
+        assert_eq!(super::time_at(10., 0.5, 2), 2);
+        // In the margin of half a time step before the first one
+        assert_eq!(super::time_at(-0.2, 0.5, 2), 0);
+    }
 }
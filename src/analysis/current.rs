@@ -1,8 +1,16 @@
 use plotters::prelude::*;
-use plotters_canvas::CanvasBackend;
 use splines::Spline;
 use yew::prelude::*;
 
+use super::reference::Reference;
+use super::zoom::Coordinates;
+use crate::configuration::settings::InterpolationKind;
+
+/// Number of samples of the spline between consecutive keys.
+const SAMPLES_PER_KEY: usize = 4;
+/// Opacity of the earliest overlaid time, which increases up to the current time.
+const MIN_OPACITY: f64 = 0.2;
+
 /// Function at a current time
 #[derive(Debug)]
 pub struct Current {
@@ -14,14 +22,30 @@ impl Current {
         Self { canvas_ref }
     }
 
-    pub fn plot(
+    /// Plots `spline`, at `current_time`, over the splines of earlier times in `overlay`.
+    ///
+    /// The initial time is drawn in red and the others in blue, fading for earlier times.
+    /// The `reference` solution, if any, is drawn in green.
+    /// Only `x_range` is drawn.
+    ///
+    /// Returns the coordinates of the chart, to map the pointer back to (x, u).
+    #[allow(clippy::too_many_arguments)]
+    pub fn plot<DB>(
         &self,
-        backend: CanvasBackend,
+        backend: DB,
         current_time: usize,
-        domain: (f64, f64),
+        time_step: f64,
+        x_range: (f64, f64),
         value_limits: (f64, f64),
+        interpolation: InterpolationKind,
         spline: &Spline<f64, f64>,
-    ) {
+        overlay: &[(usize, &Spline<f64, f64>)],
+        reference: Option<&Reference>,
+    ) -> anyhow::Result<Coordinates>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
         // Pre-computations
         let (mut min, mut max) = value_limits;
         log::trace!("min/max values of the plot: ({}, {})", min, max,);
@@ -35,10 +59,15 @@ impl Current {
             max = 1.;
             log::warn!("max value changed to {}", max);
         }
+        if min >= max {
+            log::debug!("Constant values, widening the limits");
+            min -= 0.5;
+            max += 0.5;
+        }
 
         // Plot spline
         let root = backend.into_drawing_area();
-        root.fill(&WHITE).unwrap();
+        root.fill(&WHITE)?;
 
         // Plottings
 
@@ -46,21 +75,63 @@ impl Current {
         let mut chart_builder = ChartBuilder::on(&root);
         chart_builder.set_label_area_size(LabelAreaPosition::Bottom, 40);
         chart_builder.set_label_area_size(LabelAreaPosition::Left, 40);
-        let title = format!("Time {}", current_time);
+        let title = format!("Time {} ({})", current_time, interpolation);
         chart_builder.caption(title, ("Arial", 30));
 
         let delta = max - min;
-        let mut chart = chart_builder
-            .build_cartesian_2d(
-                domain.0..domain.1,
-                (min - delta / 100.)..(max + delta / 100.),
-            )
-            .unwrap();
+        let mut chart = chart_builder.build_cartesian_2d(
+            x_range.0..x_range.1,
+            (min - delta / 100.)..(max + delta / 100.),
+        )?;
 
         let mut mesh_style = chart.configure_mesh();
-        mesh_style.draw().unwrap();
+        mesh_style.draw()?;
+        let samples = |spline: &Spline<f64, f64>| {
+            itertools_num::linspace(x_range.0, x_range.1, keys.len() * SAMPLES_PER_KEY)
+                .filter_map(|x| Some((x, spline.clamped_sample(x)?)))
+                .collect::<Vec<_>>()
+        };
+        let label = |time: usize| format!("t = {:.3}", time as f64 * time_step);
+
+        for (k, &(time, spline)) in overlay.iter().enumerate() {
+            let color = if time == 0 {
+                RED.to_rgba()
+            } else {
+                let opacity = MIN_OPACITY + (1. - MIN_OPACITY) * k as f64 / overlay.len() as f64;
+                BLUE.mix(opacity)
+            };
+            chart
+                .draw_series(LineSeries::new(samples(spline), &color))?
+                .label(label(time))
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+        }
+        if let Some(reference) = reference {
+            let t = current_time as f64 * time_step;
+            let reference_samples =
+                itertools_num::linspace(x_range.0, x_range.1, keys.len() * SAMPLES_PER_KEY)
+                    .map(|x| (x, reference.eval(x, t)))
+                    .filter(|(_, u)| u.is_finite());
+            chart
+                .draw_series(LineSeries::new(reference_samples, &GREEN))?
+                .label("Reference")
+                .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], GREEN));
+        }
         chart
-            .draw_series(LineSeries::new(keys.iter().map(|k| (k.t, k.value)), &BLACK))
-            .unwrap();
+            .draw_series(LineSeries::new(samples(spline), &BLACK))?
+            .label(label(current_time))
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLACK));
+        if !overlay.is_empty() || reference.is_some() {
+            chart
+                .configure_series_labels()
+                .background_style(WHITE.mix(0.8))
+                .border_style(BLACK)
+                .draw()?;
+        }
+
+        let coordinates = Coordinates::new(chart.as_coord_spec());
+
+        root.present()?;
+
+        Ok(coordinates)
     }
 }
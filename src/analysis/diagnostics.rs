@@ -0,0 +1,219 @@
+//! Quantities of each time, to follow conservation laws and numerical issues
+
+use plotters::prelude::*;
+use splines::Spline;
+use yew::prelude::*;
+
+/// Quantity computed from the values at one time.
+#[derive(Debug, Clone, Copy, PartialEq, strum::Display, strum::EnumIter, strum::EnumString)]
+pub enum Quantity {
+    /// Total heat, ∫u
+    #[strum(serialize = "Total heat")]
+    Integral,
+    Mean,
+    /// Variance of |u| as a distribution of heat
+    Variance,
+    Max,
+    Min,
+    #[strum(serialize = "L2 norm")]
+    L2,
+    /// ∫u_x²
+    Energy,
+}
+
+/// Quantities of the values at one time, integrated by the trapezoidal rule.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Diagnostics {
+    pub integral: f64,
+    pub mean: f64,
+    /// Not a number if u is zero everywhere, as the distribution has no mass.
+    pub variance: f64,
+    pub max: f64,
+    pub min: f64,
+    pub l2: f64,
+    pub energy: f64,
+}
+
+impl Diagnostics {
+    pub fn new(spline: &Spline<f64, f64>) -> Self {
+        let points: Vec<(f64, f64)> = spline.keys().iter().map(|k| (k.t, k.value)).collect();
+        let integrate = |f: &dyn Fn(f64, f64) -> f64| -> f64 {
+            points
+                .windows(2)
+                .map(|w| (f(w[0].0, w[0].1) + f(w[1].0, w[1].1)) / 2. * (w[1].0 - w[0].0))
+                .sum()
+        };
+
+        let integral = integrate(&|_, u| u);
+        let length = points.last().map_or(0., |p| p.0) - points.first().map_or(0., |p| p.0);
+        let max = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+        let min = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+        // Weighting by |u| keeps the variance positive when u changes sign
+        let mass = integrate(&|_, u| u.abs());
+        let variance = if mass > f64::EPSILON * length * max.abs().max(min.abs()) {
+            let center = integrate(&|x, u| x * u.abs()) / mass;
+            integrate(&|x, u| (x - center).powi(2) * u.abs()) / mass
+        } else {
+            f64::NAN
+        };
+        Diagnostics {
+            integral,
+            mean: integral / length,
+            variance,
+            max,
+            min,
+            l2: integrate(&|_, u| u * u).sqrt(),
+            energy: points
+                .windows(2)
+                .map(|w| (w[1].1 - w[0].1).powi(2) / (w[1].0 - w[0].0))
+                .sum(),
+        }
+    }
+
+    pub fn get(&self, quantity: Quantity) -> f64 {
+        match quantity {
+            Quantity::Integral => self.integral,
+            Quantity::Mean => self.mean,
+            Quantity::Variance => self.variance,
+            Quantity::Max => self.max,
+            Quantity::Min => self.min,
+            Quantity::L2 => self.l2,
+            Quantity::Energy => self.energy,
+        }
+    }
+}
+
+/// Evolution of a quantity
+#[derive(Debug)]
+pub struct DiagnosticsPlot {
+    pub canvas_ref: NodeRef,
+}
+
+impl DiagnosticsPlot {
+    pub fn new(canvas_ref: NodeRef) -> Self {
+        Self { canvas_ref }
+    }
+
+    /// Plots `quantity` over time, marking `current_time`.
+    ///
+    /// `diagnostics` has an entry per computed time, in order.
+    pub fn plot<DB>(
+        &self,
+        backend: DB,
+        current_time: usize,
+        time_step: f64,
+        quantity: Quantity,
+        diagnostics: &[Diagnostics],
+    ) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
+        let points: Vec<(f64, f64)> = diagnostics
+            .iter()
+            .enumerate()
+            .map(|(time, d)| (time as f64 * time_step, d.get(quantity)))
+            .collect();
+        let finite = || points.iter().filter(|p| p.1.is_finite());
+        let (min, max) = finite().fold((f64::INFINITY, f64::NEG_INFINITY), |(a, b), p| {
+            (a.min(p.1), b.max(p.1))
+        });
+
+        let root = backend.into_drawing_area();
+        root.fill(&WHITE)?;
+        if min > max {
+            // Like the variance without any heat
+            let area = root.titled(&quantity.to_string(), ("Arial", 20))?;
+            area.draw(&Text::new("Not defined", (10, 10), ("sans-serif", 15)))?;
+            root.present()?;
+            return Ok(());
+        }
+        // Conserved quantities would otherwise show rounding errors as large changes
+        let margin = ((max - min) / 10.)
+            .max(max.abs().max(min.abs()) * 1e-3)
+            .max(1e-9);
+        let t_max = (points.len().max(2) - 1) as f64 * time_step;
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption(quantity.to_string(), ("Arial", 20))
+            .margin(5)
+            .set_label_area_size(LabelAreaPosition::Bottom, 35)
+            .set_label_area_size(LabelAreaPosition::Left, 60)
+            .build_cartesian_2d(0f64..t_max, (min - margin)..(max + margin))?;
+        chart.configure_mesh().x_desc("t").draw()?;
+        chart.draw_series(LineSeries::new(finite().copied(), &BLACK))?;
+        chart.draw_series(
+            points
+                .get(current_time)
+                .filter(|p| p.1.is_finite())
+                .map(|&p| Circle::new(p, 4, RED.filled())),
+        )?;
+
+        root.present()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use splines::{Interpolation, Key};
+
+    #[test]
+    fn quantities() -> anyhow::Result<()> {
+        // Absolute value on [-1, 1]
+        let spline = Spline::from_vec(
+            [(-1., 1.), (0., 0.), (1., 1.)]
+                .iter()
+                .map(|&(x, u)| Key::new(x, u, Interpolation::Linear))
+                .collect(),
+        );
+        let diagnostics = Diagnostics::new(&spline);
+        assert_eq!(diagnostics.integral, 1.);
+        assert_eq!(diagnostics.mean, 0.5);
+        assert_eq!(diagnostics.variance, 1.);
+        assert_eq!((diagnostics.min, diagnostics.max), (0., 1.));
+        assert_eq!(diagnostics.l2, 1.);
+        assert_eq!(diagnostics.energy, 2.);
+        assert_eq!(diagnostics.get(Quantity::Energy), 2.);
+
+        // Identity on [-1, 1], whose total heat is zero
+        let spline = Spline::from_vec(
+            [(-1., -1.), (0., 0.), (1., 1.)]
+                .iter()
+                .map(|&(x, u)| Key::new(x, u, Interpolation::Linear))
+                .collect(),
+        );
+        let diagnostics = Diagnostics::new(&spline);
+        assert_eq!(diagnostics.integral, 0.);
+        assert_eq!(diagnostics.variance, 1.);
+
+        // Zero everywhere, without any mass
+        let spline = Spline::from_vec(
+            [(-1., 0.), (1., 0.)]
+                .iter()
+                .map(|&(x, u)| Key::new(x, u, Interpolation::Linear))
+                .collect(),
+        );
+        let diagnostics = Diagnostics::new(&spline);
+        assert_eq!(diagnostics.integral, 0.);
+        assert!(diagnostics.variance.is_nan());
+        let plot = |quantity| -> anyhow::Result<String> {
+            let mut svg = String::new();
+            DiagnosticsPlot::new(NodeRef::default()).plot(
+                SVGBackend::with_string(&mut svg, (360, 240)),
+                0,
+                1.,
+                quantity,
+                &[diagnostics; 2],
+            )?;
+            Ok(svg)
+        };
+        assert!(plot(Quantity::Max)?.contains("<circle"));
+        let undefined = plot(Quantity::Variance)?;
+        assert!(undefined.contains("Not defined"));
+        assert!(!undefined.contains("<circle"));
+        Ok(())
+    }
+}
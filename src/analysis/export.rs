@@ -0,0 +1,245 @@
+//! Export of computed values
+
+use itertools::Itertools;
+use plotters::prelude::SVGBackend;
+use serde::Serialize;
+use splines::Spline;
+use std::io::Cursor;
+
+use crate::analysis::cummulative;
+
+#[derive(Debug, Clone, Copy, PartialEq, strum::Display, strum::EnumIter)]
+pub enum ExportFormat {
+    #[strum(serialize = "CSV")]
+    Csv,
+    #[strum(serialize = "RON")]
+    Ron,
+    #[strum(serialize = "JSON")]
+    Json,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ron => "ron",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    pub fn mime(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv",
+            ExportFormat::Ron => "text/plain",
+            ExportFormat::Json => "application/json",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, strum::Display, strum::EnumIter)]
+pub enum Plot {
+    #[strum(serialize = "current")]
+    Current,
+    #[strum(serialize = "cummulative")]
+    Cummulative,
+    #[strum(serialize = "surface")]
+    Surface,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, strum::Display, strum::EnumIter)]
+pub enum ImageFormat {
+    #[strum(serialize = "PNG")]
+    Png,
+    #[strum(serialize = "SVG")]
+    Svg,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Svg => "svg",
+        }
+    }
+
+    pub fn mime(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "image/png",
+            ImageFormat::Svg => "image/svg+xml",
+        }
+    }
+}
+
+/// Backend of raster images.
+///
+/// In the browser, it is an offscreen canvas, as the bitmap backend cannot draw text there.
+#[cfg(target_arch = "wasm32")]
+pub type Raster<'a> = plotters_canvas::CanvasBackend;
+/// Backend of raster images.
+#[cfg(not(target_arch = "wasm32"))]
+pub type Raster<'a> = plotters::prelude::BitMapBackend<'a>;
+
+/// Draws with `plot` on a raster image of the given size, and returns its RGBA pixels.
+#[cfg(target_arch = "wasm32")]
+pub fn rasterize<F>(size: (u32, u32), plot: F) -> anyhow::Result<Vec<u8>>
+where
+    F: FnOnce(Raster) -> anyhow::Result<()>,
+{
+    use wasm_bindgen::JsCast;
+    use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+    let canvas: HtmlCanvasElement = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.create_element("canvas").ok())
+        .and_then(|element| element.dyn_into().ok())
+        .ok_or_else(|| anyhow::anyhow!("Could not create a canvas"))?;
+    canvas.set_width(size.0);
+    canvas.set_height(size.1);
+    let context: CanvasRenderingContext2d = canvas
+        .get_context("2d")
+        .ok()
+        .flatten()
+        .and_then(|context| context.dyn_into().ok())
+        .ok_or_else(|| anyhow::anyhow!("Could not draw on a canvas"))?;
+    let backend = plotters_canvas::CanvasBackend::with_canvas_object(canvas)
+        .ok_or_else(|| anyhow::anyhow!("Could not draw on a canvas"))?;
+    plot(backend)?;
+    let image = context
+        .get_image_data(0., 0., size.0.into(), size.1.into())
+        .map_err(|_| anyhow::anyhow!("Could not read the pixels of a canvas"))?;
+    Ok(image.data().0)
+}
+
+/// Draws with `plot` on a raster image of the given size, and returns its RGBA pixels.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn rasterize<F>(size: (u32, u32), plot: F) -> anyhow::Result<Vec<u8>>
+where
+    F: FnOnce(Raster) -> anyhow::Result<()>,
+{
+    let mut buffer = vec![0; (size.0 * size.1 * 3) as usize];
+    plot(Raster::with_buffer(&mut buffer, size))?;
+    Ok(buffer
+        .chunks(3)
+        .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], u8::MAX])
+        .collect())
+}
+
+/// Draws with `plot` on a PNG image of the given size.
+pub fn to_png<F>(size: (u32, u32), plot: F) -> anyhow::Result<Vec<u8>>
+where
+    F: FnOnce(Raster) -> anyhow::Result<()>,
+{
+    let image = image::RgbaImage::from_raw(size.0, size.1, rasterize(size, plot)?)
+        .ok_or_else(|| anyhow::anyhow!("The image does not fit its size"))?;
+    let mut bytes = Cursor::new(vec![]);
+    image.write_to(&mut bytes, image::ImageOutputFormat::Png)?;
+    Ok(bytes.into_inner())
+}
+
+/// Draws with `plot` on a SVG image of the given size.
+pub fn to_svg<F>(size: (u32, u32), plot: F) -> anyhow::Result<Vec<u8>>
+where
+    F: FnOnce(SVGBackend) -> anyhow::Result<()>,
+{
+    let mut string = String::new();
+    plot(SVGBackend::with_string(&mut string, size))?;
+    Ok(string.into_bytes())
+}
+
+/// Computed values, with their coordinates.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Results {
+    /// Space grid
+    pub x: Vec<f64>,
+    /// Time of each slice
+    pub t: Vec<f64>,
+    /// Values, indexed by space and then by time
+    pub u: Vec<Vec<f64>>,
+}
+
+impl Results {
+    /// Values of `splines`, a spline per time from 0 by `time_step`.
+    pub fn new(splines: &[Spline<f64, f64>], time_step: f64) -> Self {
+        let matrix = cummulative::matrix(splines);
+        Results {
+            x: splines[0].keys().iter().map(|k| k.t).collect(),
+            t: (0..splines.len()).map(|j| j as f64 * time_step).collect(),
+            u: matrix
+                .row_iter()
+                .map(|row| row.iter().copied().collect())
+                .collect(),
+        }
+    }
+
+    /// Table with a row per point of the grid and a column per time.
+    ///
+    /// The header contains the times.
+    pub fn to_csv(&self) -> String {
+        let header = std::iter::once("x".to_string())
+            .chain(self.t.iter().map(f64::to_string))
+            .join(",");
+        let rows = self.x.iter().zip(&self.u).map(|(x, values)| {
+            std::iter::once(x)
+                .chain(values)
+                .map(f64::to_string)
+                .join(",")
+        });
+        std::iter::once(header)
+            .chain(rows)
+            .map(|line| line + "\n")
+            .collect()
+    }
+
+    pub fn to_bytes(&self, format: ExportFormat) -> anyhow::Result<Vec<u8>> {
+        let string = match format {
+            ExportFormat::Csv => self.to_csv(),
+            ExportFormat::Ron => ron::ser::to_string_pretty(self, Default::default())?,
+            ExportFormat::Json => serde_json::to_string_pretty(self)?,
+        };
+        Ok(string.into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plotters::prelude::*;
+    use splines::{Interpolation, Key};
+
+    #[test]
+    fn images() -> anyhow::Result<()> {
+        let png = to_png((120, 40), |backend| {
+            let root = backend.into_drawing_area();
+            root.fill(&WHITE)?;
+            root.titled("Caption", ("sans-serif", 20))?;
+            Ok(())
+        })?;
+        assert_eq!(&png[1..4], b"PNG");
+        let image = image::load_from_memory(&png)?.to_rgba8();
+        assert_eq!(image.dimensions(), (120, 40));
+        assert!(image.pixels().any(|pixel| pixel.0 != [255; 4]));
+
+        let svg = to_svg((4, 3), |backend| {
+            backend.into_drawing_area().fill(&RED)?;
+            Ok(())
+        })?;
+        assert!(String::from_utf8(svg)?.starts_with("<svg"));
+        Ok(())
+    }
+
+    #[test]
+    fn csv() {
+        let splines: Vec<Spline<f64, f64>> = (0..2)
+            .map(|j| {
+                Spline::from_vec(
+                    (0..3)
+                        .map(|i| Key::new(i as f64, (i + j) as f64, Interpolation::Linear))
+                        .collect(),
+                )
+            })
+            .collect();
+        let results = Results::new(&splines, 0.5);
+        assert_eq!(results.u, vec![vec![0., 1.], vec![1., 2.], vec![2., 3.]]);
+        assert_eq!(results.to_csv(), "x,0,0.5\n0,0,1\n1,1,2\n2,2,3\n");
+    }
+}
@@ -0,0 +1,254 @@
+//! Options of the heatmap colors
+
+use yew::prelude::*;
+
+use super::select;
+
+/// Gradients of `colorous`, by name.
+macro_rules! colormaps {
+    ($($name:ident => $gradient:ident,)*) => {
+        /// Gradient of colors of the heatmap.
+        #[derive(
+            Debug, Clone, Copy, PartialEq, strum::Display, strum::EnumIter, strum::EnumString,
+        )]
+        pub enum Colormap {
+            $($name,)*
+        }
+
+        impl Colormap {
+            pub fn gradient(&self) -> colorous::Gradient {
+                match self {
+                    $(Colormap::$name => colorous::$gradient,)*
+                }
+            }
+        }
+    };
+}
+
+colormaps! {
+    Viridis => VIRIDIS,
+    Magma => MAGMA,
+    Inferno => INFERNO,
+    Plasma => PLASMA,
+    Cividis => CIVIDIS,
+    Turbo => TURBO,
+    Warm => WARM,
+    Cool => COOL,
+    Cubehelix => CUBEHELIX,
+    Rainbow => RAINBOW,
+    Sinebow => SINEBOW,
+    Blues => BLUES,
+    Greens => GREENS,
+    Greys => GREYS,
+    Oranges => ORANGES,
+    Purples => PURPLES,
+    Reds => REDS,
+    BlueGreen => BLUE_GREEN,
+    BluePurple => BLUE_PURPLE,
+    GreenBlue => GREEN_BLUE,
+    OrangeRed => ORANGE_RED,
+    PurpleBlueGreen => PURPLE_BLUE_GREEN,
+    PurpleBlue => PURPLE_BLUE,
+    PurpleRed => PURPLE_RED,
+    RedPurple => RED_PURPLE,
+    YellowGreenBlue => YELLOW_GREEN_BLUE,
+    YellowGreen => YELLOW_GREEN,
+    YellowOrangeBrown => YELLOW_ORANGE_BROWN,
+    YellowOrangeRed => YELLOW_ORANGE_RED,
+    // Diverging, for signed values
+    BrownGreen => BROWN_GREEN,
+    PurpleGreen => PURPLE_GREEN,
+    PinkGreen => PINK_GREEN,
+    PurpleOrange => PURPLE_ORANGE,
+    RedBlue => RED_BLUE,
+    RedGrey => RED_GREY,
+    RedYellowBlue => RED_YELLOW_BLUE,
+    RedYellowGreen => RED_YELLOW_GREEN,
+    Spectral => SPECTRAL,
+}
+
+/// How values are mapped to the colormap.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, strum::Display, strum::EnumIter, strum::EnumString,
+)]
+pub enum Scaling {
+    #[default]
+    Linear,
+    /// Logarithmic, for positive values
+    Log,
+    /// Linear, with zero at the center of the colormap
+    Symmetric,
+}
+
+#[derive(Debug)]
+pub enum SetHeatmap {
+    Colormap(ChangeData),
+    Highlight(ChangeData),
+    Scaling(ChangeData),
+    Min(ChangeData),
+    Max(ChangeData),
+}
+
+/// Options of the heatmap colors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeatmapOptions {
+    pub colormap: Colormap,
+    /// Colormap of the current time.
+    pub highlight: Colormap,
+    pub scaling: Scaling,
+    /// Fixed limits of the values, instead of the limits of the computed values.
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl Default for HeatmapOptions {
+    fn default() -> Self {
+        Self {
+            colormap: Colormap::Viridis,
+            highlight: Colormap::Magma,
+            scaling: Scaling::default(),
+            min: None,
+            max: None,
+        }
+    }
+}
+
+/// Smallest value of the logarithmic scale, relative to the largest.
+const LOG_RANGE: f64 = 1e-3;
+
+impl HeatmapOptions {
+    pub fn update(&mut self, set: SetHeatmap) -> ShouldRender {
+        match set {
+            SetHeatmap::Colormap(data) => match select(data) {
+                Some(colormap) => {
+                    self.colormap = colormap;
+                    true
+                }
+                None => false,
+            },
+            SetHeatmap::Highlight(data) => match select(data) {
+                Some(highlight) => {
+                    self.highlight = highlight;
+                    true
+                }
+                None => false,
+            },
+            SetHeatmap::Scaling(data) => match select(data) {
+                Some(scaling) => {
+                    self.scaling = scaling;
+                    true
+                }
+                None => false,
+            },
+            SetHeatmap::Min(ChangeData::Value(x)) => match parse_limit(&x) {
+                Some(min) => {
+                    self.min = min;
+                    true
+                }
+                None => false,
+            },
+            SetHeatmap::Max(ChangeData::Value(x)) => match parse_limit(&x) {
+                Some(max) => {
+                    self.max = max;
+                    true
+                }
+                None => false,
+            },
+            set => {
+                log::error!("Tried to change heatmap options with {:?}", set);
+                false
+            }
+        }
+    }
+
+    /// Range of values covered by the colormap, given the limits of the computed values.
+    pub fn range(&self, limit_values: (f64, f64)) -> (f64, f64) {
+        let (mut min, mut max) = (
+            self.min.unwrap_or(limit_values.0),
+            self.max.unwrap_or(limit_values.1),
+        );
+        match self.scaling {
+            Scaling::Linear => {}
+            Scaling::Log => {
+                if max <= 0. {
+                    log::warn!("Logarithmic scale without positive values");
+                    max = 1.;
+                }
+                if min <= 0. {
+                    min = max * LOG_RANGE;
+                }
+            }
+            Scaling::Symmetric => {
+                max = min.abs().max(max.abs());
+                min = -max;
+            }
+        }
+        if min >= max || !(min.is_finite() && max.is_finite()) {
+            log::debug!("Degenerate color range ({}, {}), widening it", min, max);
+            (min, max) = match self.scaling {
+                Scaling::Log => (0.5, 2.),
+                _ if min.is_finite() => (min - 0.5, min + 0.5),
+                _ => (-1., 1.),
+            };
+        }
+        (min, max)
+    }
+
+    /// Position of `value` in the colormap, from 0 to 1, where `range` is given by `range`.
+    pub fn position(&self, value: f64, range: (f64, f64)) -> f64 {
+        let position = match self.scaling {
+            Scaling::Log => (value.ln() - range.0.ln()) / (range.1.ln() - range.0.ln()),
+            Scaling::Linear | Scaling::Symmetric => (value - range.0) / (range.1 - range.0),
+        };
+        if position.is_nan() {
+            0.
+        } else {
+            position.clamp(0., 1.)
+        }
+    }
+}
+
+/// Parses a fixed limit, where an empty input means automatic.
+fn parse_limit(x: &str) -> Option<Option<f64>> {
+    if x.trim().is_empty() {
+        return Some(None);
+    }
+    match x.trim().parse::<f64>() {
+        Ok(limit) if limit.is_finite() => Some(Some(limit)),
+        _ => {
+            log::warn!("Invalid heatmap limit: {}", x);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaling() {
+        let mut options = HeatmapOptions::default();
+        assert_eq!(options.range((-1., 3.)), (-1., 3.));
+        assert_eq!(options.position(1., (-1., 3.)), 0.5);
+        assert_eq!(options.position(5., (-1., 3.)), 1.);
+        assert_eq!(options.range((2., 2.)), (1.5, 2.5));
+
+        options.scaling = Scaling::Symmetric;
+        assert_eq!(options.range((-1., 3.)), (-3., 3.));
+        assert_eq!(options.position(0., (-3., 3.)), 0.5);
+
+        options.scaling = Scaling::Log;
+        let range = options.range((-1., 10.));
+        assert_eq!(range, (10. * LOG_RANGE, 10.));
+        assert!((options.position(1., (0.1, 10.)) - 0.5).abs() < 1e-12);
+        assert_eq!(options.position(-1., range), 0.);
+
+        options.scaling = Scaling::Linear;
+        options.min = Some(0.);
+        options.max = Some(1.);
+        assert_eq!(options.range((-1., 3.)), (0., 1.));
+        assert_eq!(parse_limit(" "), Some(None));
+        assert_eq!(parse_limit("x"), None);
+    }
+}
@@ -0,0 +1,277 @@
+//! Size of the plots, following the width of the page and the resolution of the screen
+
+use plotters::prelude::DrawingBackend;
+use plotters_backend::{
+    BackendColor, BackendCoord, BackendStyle, BackendTextStyle, DrawingErrorKind,
+};
+use plotters_canvas::CanvasBackend;
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{
+    CanvasRenderingContext2d, Element, HtmlCanvasElement, MediaQueryList, ResizeObserver,
+    ResizeObserverEntry,
+};
+
+/// Narrowest plot before they are stacked instead of side by side.
+const MIN_PLOT_WIDTH: f64 = 300.;
+
+/// Size of the plots, in CSS pixels, and density of the screen.
+///
+/// Unlike the settings, it is not stored, as it depends on the window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Layout {
+    /// Width available for the plots.
+    pub width: f64,
+    /// Device pixels per CSS pixel.
+    pub pixel_ratio: f64,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            width: 720.,
+            pixel_ratio: 1.,
+        }
+    }
+}
+
+impl Layout {
+    /// Layout of `width`, at the current resolution of the screen.
+    pub fn new(width: f64) -> Self {
+        Self {
+            width,
+            pixel_ratio: web_sys::window().map_or(1., |w| w.device_pixel_ratio()),
+        }
+    }
+
+    /// Size of a main plot, two of which fit side by side if there is room for them.
+    pub fn plot_size(&self) -> (u32, u32) {
+        let columns = if self.width >= 2. * MIN_PLOT_WIDTH {
+            2.
+        } else {
+            1.
+        };
+        let side = (self.width / columns).floor().max(1.) as u32;
+        (side, side)
+    }
+
+    /// Sets the displayed size of `canvas` to `size`, with one pixel per device pixel.
+    pub fn resize(&self, canvas: &HtmlCanvasElement, size: (u32, u32)) {
+        let pixels = self.pixels(size);
+        if (canvas.width(), canvas.height()) != pixels {
+            canvas.set_width(pixels.0);
+            canvas.set_height(pixels.1);
+        }
+        let style = canvas.style();
+        for (property, value) in [("width", size.0), ("height", size.1)] {
+            if let Err(e) = style.set_property(property, &format!("{}px", value)) {
+                log::error!("Could not set the {} of a canvas: {:?}", property, e);
+            }
+        }
+    }
+
+    /// Number of device pixels of `size`.
+    fn pixels(&self, size: (u32, u32)) -> (u32, u32) {
+        let scale = |x: u32| (x as f64 * self.pixel_ratio).round().max(1.) as u32;
+        (scale(size.0), scale(size.1))
+    }
+}
+
+/// Backend drawing on a canvas in CSS pixels, whatever its number of device pixels.
+///
+/// The context of the canvas is scaled, so that lines and text stay sharp on dense screens.
+pub struct ScaledCanvas {
+    inner: CanvasBackend,
+    size: (u32, u32),
+}
+
+impl ScaledCanvas {
+    /// Backend of a canvas sized by `layout`.
+    pub fn new(canvas: HtmlCanvasElement, layout: &Layout) -> Option<Self> {
+        let ratio = layout.pixel_ratio;
+        let context: CanvasRenderingContext2d = canvas.get_context("2d").ok()??.dyn_into().ok()?;
+        context.set_transform(ratio, 0., 0., ratio, 0., 0.).ok()?;
+        let size = (
+            (canvas.width() as f64 / ratio).round() as u32,
+            (canvas.height() as f64 / ratio).round() as u32,
+        );
+        Some(Self {
+            inner: CanvasBackend::with_canvas_object(canvas)?,
+            size,
+        })
+    }
+}
+
+type Error = DrawingErrorKind<<CanvasBackend as DrawingBackend>::ErrorType>;
+
+impl DrawingBackend for ScaledCanvas {
+    type ErrorType = <CanvasBackend as DrawingBackend>::ErrorType;
+
+    fn get_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn ensure_prepared(&mut self) -> Result<(), Error> {
+        self.inner.ensure_prepared()
+    }
+
+    fn present(&mut self) -> Result<(), Error> {
+        self.inner.present()
+    }
+
+    fn draw_pixel(&mut self, point: BackendCoord, color: BackendColor) -> Result<(), Error> {
+        self.inner.draw_pixel(point, color)
+    }
+
+    fn draw_line<S: BackendStyle>(
+        &mut self,
+        from: BackendCoord,
+        to: BackendCoord,
+        style: &S,
+    ) -> Result<(), Error> {
+        self.inner.draw_line(from, to, style)
+    }
+
+    fn draw_rect<S: BackendStyle>(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), Error> {
+        self.inner.draw_rect(upper_left, bottom_right, style, fill)
+    }
+
+    fn draw_path<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        path: I,
+        style: &S,
+    ) -> Result<(), Error> {
+        self.inner.draw_path(path, style)
+    }
+
+    fn draw_circle<S: BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        radius: u32,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), Error> {
+        self.inner.draw_circle(center, radius, style, fill)
+    }
+
+    fn fill_polygon<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        vert: I,
+        style: &S,
+    ) -> Result<(), Error> {
+        self.inner.fill_polygon(vert, style)
+    }
+
+    fn draw_text<S: BackendTextStyle>(
+        &mut self,
+        text: &str,
+        style: &S,
+        pos: BackendCoord,
+    ) -> Result<(), Error> {
+        self.inner.draw_text(text, style, pos)
+    }
+
+    fn estimate_text_size<S: BackendTextStyle>(
+        &self,
+        text: &str,
+        style: &S,
+    ) -> Result<(u32, u32), Error> {
+        self.inner.estimate_text_size(text, style)
+    }
+}
+
+/// Reports the width of an element whenever it changes, until dropped.
+#[derive(Debug)]
+pub struct ResizeListener {
+    observer: ResizeObserver,
+    _callback: Closure<dyn FnMut(js_sys::Array)>,
+}
+
+impl ResizeListener {
+    pub fn new(element: &Element, mut on_resize: impl FnMut(f64) + 'static) -> Option<Self> {
+        let callback = Closure::wrap(Box::new(move |entries: js_sys::Array| {
+            if let Ok(entry) = entries.get(0).dyn_into::<ResizeObserverEntry>() {
+                on_resize(entry.content_rect().width());
+            }
+        }) as Box<dyn FnMut(js_sys::Array)>);
+        let observer = ResizeObserver::new(callback.as_ref().unchecked_ref()).ok()?;
+        observer.observe(element);
+        Some(Self {
+            observer,
+            _callback: callback,
+        })
+    }
+}
+
+impl Drop for ResizeListener {
+    fn drop(&mut self) {
+        self.observer.disconnect();
+    }
+}
+
+/// Reports when the density of the screen is no longer `pixel_ratio`, until dropped.
+///
+/// Moving the window to another screen changes it without resizing the page.
+#[derive(Debug)]
+pub struct PixelRatioListener {
+    pub pixel_ratio: f64,
+    query: MediaQueryList,
+    callback: Closure<dyn FnMut()>,
+}
+
+impl PixelRatioListener {
+    pub fn new(pixel_ratio: f64, on_change: impl FnMut() + 'static) -> Option<Self> {
+        let query = web_sys::window()?
+            .match_media(&format!("(resolution: {}dppx)", pixel_ratio))
+            .ok()??;
+        let callback = Closure::wrap(Box::new(on_change) as Box<dyn FnMut()>);
+        query
+            .add_event_listener_with_callback("change", callback.as_ref().unchecked_ref())
+            .ok()?;
+        Some(Self {
+            pixel_ratio,
+            query,
+            callback,
+        })
+    }
+}
+
+impl Drop for PixelRatioListener {
+    fn drop(&mut self) {
+        let callback = self.callback.as_ref().unchecked_ref();
+        if let Err(e) = self
+            .query
+            .remove_event_listener_with_callback("change", callback)
+        {
+            log::error!(
+                "Could not stop following the density of the screen: {:?}",
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes() {
+        let layout = Layout {
+            width: 800.,
+            pixel_ratio: 1.5,
+        };
+        assert_eq!(layout.plot_size(), (400, 400));
+        assert_eq!(layout.pixels((400, 200)), (600, 300));
+        let narrow = Layout {
+            width: 450.5,
+            ..layout
+        };
+        assert_eq!(narrow.plot_size(), (450, 450));
+    }
+}
@@ -0,0 +1,103 @@
+//! Limits of the computed values, used to scale the plots
+
+use splines::Spline;
+
+/// How the limits of the plots follow the computed values.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, strum::Display, strum::EnumIter, strum::EnumString,
+)]
+pub enum Autoscale {
+    /// Limits of the initial conditions
+    #[default]
+    #[strum(serialize = "Fixed from initial")]
+    Initial,
+    /// Limits of all computed times
+    Global,
+    /// Limits of each time on its own
+    #[strum(serialize = "Per frame")]
+    PerFrame,
+}
+
+/// Minimum and maximum of the computed values, updated as new times are computed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Limits {
+    /// Limits of each computed time.
+    frames: Vec<(f64, f64)>,
+    /// Limits of all computed times.
+    global: (f64, f64),
+}
+
+impl Limits {
+    /// Limits of `values`, a spline per computed time, in order.
+    pub fn new(values: &[Spline<f64, f64>]) -> Self {
+        let mut limits = Limits {
+            frames: vec![],
+            global: (f64::INFINITY, f64::NEG_INFINITY),
+        };
+        for spline in values {
+            limits.push(spline);
+        }
+        limits
+    }
+
+    /// Takes into account a newly computed time.
+    pub fn push(&mut self, spline: &Spline<f64, f64>) {
+        let frame = spline
+            .keys()
+            .iter()
+            .map(|key| key.value)
+            .filter(|value| value.is_finite())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+                (min.min(value), max.max(value))
+            });
+        self.global = (self.global.0.min(frame.0), self.global.1.max(frame.1));
+        self.frames.push(frame);
+    }
+
+    /// Limits for plotting `time`.
+    ///
+    /// They are not finite if there are no finite values.
+    pub fn at(&self, autoscale: Autoscale, time: usize) -> (f64, f64) {
+        match autoscale {
+            Autoscale::Initial => self.frames[0],
+            Autoscale::Global => self.global,
+            Autoscale::PerFrame => self.frames[time],
+        }
+    }
+
+    /// Limits for plotting each computed time.
+    pub fn frames(&self, autoscale: Autoscale) -> Vec<(f64, f64)> {
+        (0..self.frames.len())
+            .map(|time| self.at(autoscale, time))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use splines::{Interpolation, Key};
+
+    #[test]
+    fn autoscale() {
+        let spline = |values: &[f64]| {
+            Spline::from_vec(
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &value)| Key::new(i as f64, value, Interpolation::Linear))
+                    .collect(),
+            )
+        };
+        let mut limits = Limits::new(&[spline(&[0., 1.])]);
+        limits.push(&spline(&[-2., f64::NAN, 0.5]));
+        assert_eq!(limits.at(Autoscale::Initial, 1), (0., 1.));
+        assert_eq!(limits.at(Autoscale::Global, 1), (-2., 1.));
+        assert_eq!(limits.at(Autoscale::PerFrame, 1), (-2., 0.5));
+        assert_eq!(limits.frames(Autoscale::Global), vec![(-2., 1.); 2]);
+
+        limits.push(&spline(&[f64::NAN]));
+        assert!(!limits.at(Autoscale::PerFrame, 2).0.is_finite());
+        assert_eq!(limits.at(Autoscale::Global, 2), (-2., 1.));
+    }
+}
@@ -0,0 +1,75 @@
+//! Earlier times drawn over the current plot
+
+use yew::prelude::*;
+
+#[derive(Debug)]
+pub enum SetOverlay {
+    Initial(ChangeData),
+    Previous(ChangeData),
+}
+
+/// Earlier times to draw with the current one.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OverlayOptions {
+    /// Whether to draw the initial conditions.
+    pub initial: bool,
+    /// Number of times before the current one to draw.
+    pub previous: usize,
+}
+
+impl OverlayOptions {
+    pub fn update(&mut self, set: SetOverlay) -> ShouldRender {
+        match set {
+            SetOverlay::Initial(ChangeData::Value(_)) => {
+                self.initial = !self.initial;
+                true
+            }
+            SetOverlay::Previous(ChangeData::Value(x)) => {
+                log::trace!("Trying to overlay {} previous times", x);
+                match x.parse() {
+                    Ok(previous) => {
+                        self.previous = previous;
+                        true
+                    }
+                    Err(_) => {
+                        log::warn!("Invalid number of previous times: {}", x);
+                        false
+                    }
+                }
+            }
+            set => {
+                log::error!("Tried to change overlay options with {:?}", set);
+                false
+            }
+        }
+    }
+
+    /// Times to draw before `time`, from the earliest.
+    pub fn times(&self, time: usize) -> Vec<usize> {
+        let first_previous = time.saturating_sub(self.previous);
+        let initial = if self.initial && first_previous > 0 {
+            Some(0)
+        } else {
+            None
+        };
+        initial.into_iter().chain(first_previous..time).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn times() {
+        let mut options = OverlayOptions::default();
+        assert!(options.times(5).is_empty());
+        options.previous = 2;
+        assert_eq!(options.times(5), vec![3, 4]);
+        assert_eq!(options.times(1), vec![0]);
+        options.initial = true;
+        assert_eq!(options.times(5), vec![0, 3, 4]);
+        assert_eq!(options.times(2), vec![0, 1]);
+        assert!(options.times(0).is_empty());
+    }
+}
@@ -0,0 +1,139 @@
+//! Reference solutions, to compare with the computed values
+
+use core::str::FromStr;
+use exmex::FlatEx;
+use splines::Spline;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ReferenceError {
+    #[error("Invalid reference: {0}")]
+    Expression(String),
+    #[error("The reference may only depend on {{x}} and {{t}}")]
+    Variables,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Variable {
+    X,
+    T,
+}
+
+/// Analytical function of space `{x}` and time `{t}`.
+#[derive(Debug, Clone)]
+pub struct Reference {
+    expression: Box<FlatEx<f64>>,
+    /// Variables of `expression`, in the order of their first occurrence.
+    variables: Vec<Variable>,
+}
+
+impl FromStr for Reference {
+    type Err = ReferenceError;
+    fn from_str(s: &str) -> Result<Self, ReferenceError> {
+        let expression = exmex::parse(s, &exmex::make_default_operators::<f64>())
+            .map_err(|e| ReferenceError::Expression(e.to_string()))?;
+        let mut occurrences: Vec<(usize, Variable)> = [("{x}", Variable::X), ("{t}", Variable::T)]
+            .iter()
+            .filter_map(|&(name, variable)| Some((s.find(name)?, variable)))
+            .collect();
+        occurrences.sort_by_key(|&(position, _)| position);
+        let reference = Reference {
+            expression: Box::new(expression),
+            variables: occurrences.into_iter().map(|(_, v)| v).collect(),
+        };
+        // Any other variable makes the evaluation fail
+        reference
+            .expression
+            .eval(&vec![0.; reference.variables.len()])
+            .map_err(|_| ReferenceError::Variables)?;
+        Ok(reference)
+    }
+}
+
+/// Norms of the difference between the computed values and the reference.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Norms {
+    pub l2: f64,
+    pub linf: f64,
+}
+
+impl Reference {
+    pub fn eval(&self, x: f64, t: f64) -> f64 {
+        let values: Vec<f64> = self
+            .variables
+            .iter()
+            .map(|variable| match variable {
+                Variable::X => x,
+                Variable::T => t,
+            })
+            .collect();
+        // The number of variables is checked when parsing
+        self.expression.eval(&values).unwrap()
+    }
+
+    /// Difference between `spline` and the reference at time `t`, at the keys of `spline`.
+    pub fn errors(&self, spline: &Spline<f64, f64>, t: f64) -> Vec<(f64, f64)> {
+        spline
+            .keys()
+            .iter()
+            .map(|key| (key.t, key.value - self.eval(key.t, t)))
+            .collect()
+    }
+}
+
+impl Norms {
+    /// Norms of pointwise `errors`, with the L2 norm integrated by the trapezoidal rule.
+    pub fn new(errors: &[(f64, f64)]) -> Self {
+        let squared: f64 = errors
+            .windows(2)
+            .map(|w| (w[0].1.powi(2) + w[1].1.powi(2)) / 2. * (w[1].0 - w[0].0))
+            .sum();
+        Norms {
+            l2: squared.sqrt(),
+            linf: errors.iter().map(|(_, e)| e.abs()).fold(0., f64::max),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use splines::{Interpolation, Key};
+
+    #[test]
+    fn eval() -> anyhow::Result<()> {
+        let reference: Reference = "exp(-{t}) * sin({x})".parse()?;
+        assert_eq!(reference.eval(1., 0.), 1f64.sin());
+        assert_eq!(reference.eval(1., 2.), (-2f64).exp() * 1f64.sin());
+        let reference: Reference = "{x} - 2 * {t}".parse()?;
+        assert_eq!(reference.eval(1., 2.), -3.);
+        let reference: Reference = "2".parse()?;
+        assert_eq!(reference.eval(1., 2.), 2.);
+
+        assert!(matches!(
+            "{x} * {y}".parse::<Reference>(),
+            Err(ReferenceError::Variables)
+        ));
+        assert!(matches!(
+            "sin(".parse::<Reference>(),
+            Err(ReferenceError::Expression(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn norms() -> anyhow::Result<()> {
+        let spline = Spline::from_vec(
+            (0..3)
+                .map(|i| Key::new(i as f64, 1., Interpolation::Linear))
+                .collect(),
+        );
+        let reference: Reference = "{t}".parse()?;
+        let errors = reference.errors(&spline, 3.);
+        assert_eq!(errors, vec![(0., -2.), (1., -2.), (2., -2.)]);
+        let norms = Norms::new(&errors);
+        assert_eq!(norms.linf, 2.);
+        assert!((norms.l2 - 8f64.sqrt()).abs() < 1e-12);
+        Ok(())
+    }
+}
@@ -0,0 +1,210 @@
+//! Space-time solution drawn as a surface
+
+use itertools::Itertools;
+use plotters::prelude::*;
+use splines::Spline;
+use yew::prelude::*;
+
+use super::cummulative::matrix;
+use super::heatmap::HeatmapOptions;
+
+#[derive(Debug)]
+pub enum SetViewpoint {
+    Yaw(ChangeData),
+    Pitch(ChangeData),
+}
+
+/// Direction from which the surface is seen, in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewpoint {
+    /// Rotation around the vertical axis.
+    pub yaw: f64,
+    /// Elevation above the (x, t) plane.
+    pub pitch: f64,
+}
+
+impl Default for Viewpoint {
+    fn default() -> Self {
+        Self {
+            yaw: 30.,
+            pitch: 20.,
+        }
+    }
+}
+
+impl Viewpoint {
+    pub fn update(&mut self, set: SetViewpoint) -> ShouldRender {
+        match set {
+            SetViewpoint::Yaw(ChangeData::Value(x)) => {
+                log::trace!("Trying to change yaw to {}", x);
+                match x.parse::<f64>() {
+                    Ok(yaw) if yaw.is_finite() => {
+                        self.yaw = yaw;
+                        true
+                    }
+                    _ => {
+                        log::warn!("Invalid yaw: {}", x);
+                        false
+                    }
+                }
+            }
+            SetViewpoint::Pitch(ChangeData::Value(x)) => {
+                log::trace!("Trying to change pitch to {}", x);
+                match x.parse::<f64>() {
+                    Ok(pitch) if (-90. ..=90.).contains(&pitch) => {
+                        self.pitch = pitch;
+                        true
+                    }
+                    _ => {
+                        log::warn!("Invalid pitch: {}", x);
+                        false
+                    }
+                }
+            }
+            set => {
+                log::error!("Tried to change viewpoint with {:?}", set);
+                false
+            }
+        }
+    }
+}
+
+/// Function of space and time as a surface
+#[derive(Debug)]
+pub struct Surface {
+    pub canvas_ref: NodeRef,
+}
+
+impl Surface {
+    pub fn new(canvas_ref: NodeRef) -> Self {
+        Self { canvas_ref }
+    }
+
+    /// Plots `splines`, a spline per computed time, as a surface over space and time.
+    /// `limit_values` are the limits of the vertical axis and the colors.
+    pub fn plot<DB>(
+        &self,
+        backend: DB,
+        time_step: f64,
+        viewpoint: Viewpoint,
+        limit_values: (f64, f64),
+        options: &HeatmapOptions,
+        splines: &[Spline<f64, f64>],
+    ) -> anyhow::Result<()>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
+        let range = options.range(limit_values);
+        let space: Vec<f64> = splines[0].keys().iter().map(|key| key.t).collect();
+        let matrix = matrix(splines);
+        let (rows, columns) = matrix.shape();
+        log::trace!("Plotting a {}x{} surface", rows, columns);
+
+        let gradient = options.colormap.gradient();
+        let color = |v: f64| {
+            let color = gradient.eval_continuous(options.position(v, range));
+            RGBColor(color.r, color.g, color.b)
+        };
+        let height = |v: f64| v.clamp(range.0, range.1);
+        let t_max = (columns.max(2) - 1) as f64 * time_step;
+
+        let root = backend.into_drawing_area();
+        root.fill(&WHITE)?;
+        let mut chart = ChartBuilder::on(&root)
+            .caption("Surface u(x, t)", ("Arial", 20))
+            .margin(5)
+            .build_cartesian_3d(space[0]..space[rows - 1], range.0..range.1, 0f64..t_max)?;
+        chart.with_projection(|mut pb| {
+            pb.yaw = viewpoint.yaw.to_radians();
+            pb.pitch = viewpoint.pitch.to_radians();
+            pb.scale = 0.8;
+            pb.into_matrix()
+        });
+        chart
+            .configure_axes()
+            .light_grid_style(BLACK.mix(0.15))
+            .max_light_lines(3)
+            .draw()?;
+
+        // Each quad joins four neighbouring values, colored by their mean
+        chart.draw_series(
+            (1..rows)
+                .cartesian_product(1..columns)
+                .filter_map(|(i, j)| {
+                    let corners = [(i - 1, j - 1), (i, j - 1), (i, j), (i - 1, j)];
+                    let values = corners.map(|index| matrix[index]);
+                    if values.iter().any(|v| !v.is_finite()) {
+                        return None;
+                    }
+                    Some(Polygon::new(
+                        corners
+                            .iter()
+                            .zip(values.iter())
+                            .map(|(&(i, j), &v)| (space[i], height(v), j as f64 * time_step))
+                            .collect::<Vec<_>>(),
+                        color(values.iter().sum::<f64>() / 4.).filled(),
+                    ))
+                }),
+        )?;
+
+        root.present()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::export::to_png;
+    use crate::analysis::test_splines;
+
+    #[test]
+    fn plot() -> anyhow::Result<()> {
+        let mut svg = String::new();
+        Surface::new(NodeRef::default()).plot(
+            SVGBackend::with_string(&mut svg, (360, 360)),
+            0.5,
+            Viewpoint::default(),
+            (0., 8.),
+            &HeatmapOptions::default(),
+            &test_splines(),
+        )?;
+        assert!(svg.contains("Surface u(x, t)"));
+        // One quad per pair of neighbouring points in space and time, after the three axis panels
+        assert_eq!(svg.matches("<polygon").count(), 3 + 4 * 2);
+        // Quads are colored by their mean, (2i - 1)(2j - 1) / 4, which is 3 / 4 for two of them
+        let colors: std::collections::HashSet<&str> = svg
+            .split("<polygon")
+            .skip(4)
+            .filter_map(|polygon| polygon.split("fill=\"").nth(1))
+            .map(|fill| &fill[..7])
+            .collect();
+        assert_eq!(colors.len(), 7);
+        // The vertical axis spans the limits
+        assert!(svg.contains("\n8.0\n"));
+
+        let mut viewpoint = Viewpoint::default();
+        assert!(!viewpoint.update(SetViewpoint::Pitch(ChangeData::Value("120".into()))));
+        assert!(viewpoint.update(SetViewpoint::Yaw(ChangeData::Value("-45".into()))));
+        assert_eq!(viewpoint.yaw, -45.);
+        Ok(())
+    }
+
+    #[test]
+    fn png() -> anyhow::Result<()> {
+        let png = to_png((200, 200), |backend| {
+            Surface::new(NodeRef::default()).plot(
+                backend,
+                1.,
+                Viewpoint::default(),
+                (0., 8.),
+                &HeatmapOptions::default(),
+                &test_splines(),
+            )
+        })?;
+        assert_eq!(&png[1..4], b"PNG");
+        Ok(())
+    }
+}
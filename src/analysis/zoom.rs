@@ -0,0 +1,181 @@
+//! Zoom and pan of the x axis, and mapping of the pointer back to the plotted values
+
+use plotters::coord::cartesian::Cartesian2d;
+use plotters::coord::types::RangedCoordf64;
+use plotters::coord::ReverseCoordTranslate;
+use yew::prelude::*;
+
+/// Factor of the visible range for each step of the wheel.
+pub const ZOOM_FACTOR: f64 = 1.2;
+/// Smallest visible range, relative to the whole domain.
+const MIN_WIDTH: f64 = 1e-6;
+
+#[derive(Debug)]
+pub enum Pointer {
+    Move(MouseEvent),
+    Down(MouseEvent),
+    Up(MouseEvent),
+    Leave,
+    Wheel(WheelEvent),
+}
+
+/// Mapping from the pixels of a canvas to the values of its chart.
+#[derive(Clone)]
+pub struct Coordinates(Cartesian2d<RangedCoordf64, RangedCoordf64>);
+
+impl core::fmt::Debug for Coordinates {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Coordinates").finish()
+    }
+}
+
+impl Coordinates {
+    pub fn new(coordinates: &Cartesian2d<RangedCoordf64, RangedCoordf64>) -> Self {
+        Self(coordinates.clone())
+    }
+
+    /// Values at `pixel`, if it is inside the chart.
+    pub fn value(&self, pixel: (i32, i32)) -> Option<(f64, f64)> {
+        self.0.reverse_translate(pixel)
+    }
+}
+
+/// Visible part of the domain.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Zoom {
+    /// Visible range of x, or `None` for the whole domain.
+    pub range: Option<(f64, f64)>,
+    /// Value of x grabbed by the pointer, while dragging.
+    pub anchor: Option<f64>,
+    /// Whether the range moved since the pointer grabbed it.
+    pub panned: bool,
+}
+
+impl Zoom {
+    /// Visible range of x within `domain`.
+    pub fn range(&self, domain: (f64, f64)) -> (f64, f64) {
+        self.range.unwrap_or(domain)
+    }
+
+    /// Scales the visible range around `center` by `factor`.
+    pub fn zoom(&mut self, domain: (f64, f64), center: f64, factor: f64) {
+        let (start, end) = self.range(domain);
+        self.set(
+            domain,
+            (
+                center + (start - center) * factor,
+                center + (end - center) * factor,
+            ),
+        );
+    }
+
+    /// Shifts the visible range by `delta`.
+    pub fn pan(&mut self, domain: (f64, f64), delta: f64) {
+        let (start, end) = self.range(domain);
+        self.set(domain, (start + delta, end + delta));
+    }
+
+    /// Grabs the range at `x`, if the pointer is over the chart.
+    pub fn grab(&mut self, x: Option<f64>) {
+        self.anchor = x;
+        self.panned = false;
+    }
+
+    /// Pans the grabbed range so that its anchor is at `x`.
+    ///
+    /// Returns whether the visible range changed.
+    pub fn drag(&mut self, domain: (f64, f64), x: f64) -> bool {
+        let anchor = match self.anchor {
+            Some(anchor) => anchor,
+            None => return false,
+        };
+        let range = self.range;
+        self.pan(domain, anchor - x);
+        let moved = self.range != range;
+        self.panned |= moved;
+        moved
+    }
+
+    /// Releases the grabbed range.
+    ///
+    /// Returns whether it was a click, that is grabbed and released without panning.
+    pub fn release(&mut self) -> bool {
+        self.anchor.take().is_some() && !self.panned
+    }
+
+    /// Sets the visible range, keeping it inside `domain`.
+    fn set(&mut self, domain: (f64, f64), range: (f64, f64)) {
+        let length = domain.1 - domain.0;
+        let width = (range.1 - range.0).max(length * MIN_WIDTH);
+        self.range = if width >= length {
+            None
+        } else {
+            let start = range.0.max(domain.0).min(domain.1 - width);
+            Some((start, start + width))
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plotters::prelude::*;
+
+    #[test]
+    fn zoom() {
+        let domain = (0., 4.);
+        let mut zoom = Zoom::default();
+        zoom.zoom(domain, 1., 0.5);
+        assert_eq!(zoom.range, Some((0.5, 2.5)));
+        zoom.pan(domain, -1.);
+        assert_eq!(zoom.range, Some((0., 2.)));
+        zoom.pan(domain, 3.);
+        assert_eq!(zoom.range, Some((2., 4.)));
+        zoom.zoom(domain, 3., 4.);
+        assert_eq!(zoom.range, None);
+        assert_eq!(zoom.range(domain), domain);
+    }
+
+    #[test]
+    fn drag() {
+        let domain = (0., 4.);
+        let mut zoom = Zoom::default();
+        // Nothing is grabbed outside the chart
+        zoom.grab(None);
+        assert!(!zoom.drag(domain, 1.));
+        assert!(!zoom.release());
+
+        // The whole domain can not be panned, so releasing it is a click
+        zoom.grab(Some(1.));
+        assert!(!zoom.drag(domain, 2.));
+        assert!(zoom.release());
+        assert_eq!(zoom.anchor, None);
+
+        zoom.zoom(domain, 0., 0.5);
+        zoom.grab(Some(1.));
+        assert!(zoom.drag(domain, 0.5));
+        assert_eq!(zoom.range, Some((0.5, 2.5)));
+        // Once replotted, the anchor is under the pointer again
+        assert!(!zoom.drag(domain, 1.));
+        assert!(!zoom.release());
+        // Leaving the chart drops the grab
+        zoom.grab(Some(1.));
+        zoom.release();
+        assert!(!zoom.drag(domain, 0.));
+    }
+
+    #[test]
+    fn coordinates() -> anyhow::Result<()> {
+        let mut svg = String::new();
+        let root = SVGBackend::with_string(&mut svg, (110, 60)).into_drawing_area();
+        let (_, area) = root.split_horizontally(10);
+        let chart = ChartBuilder::on(&area).build_cartesian_2d(0f64..10f64, 0f64..1f64)?;
+        let coordinates = Coordinates::new(chart.as_coord_spec());
+        let (x, y) = coordinates.value((60, 30)).unwrap();
+        assert!((x - 5.).abs() < 0.2 && (y - 0.5).abs() < 0.05);
+        let (x, y) = coordinates.value((10, 59)).unwrap();
+        assert!(x.abs() < 0.2 && y.abs() < 0.05);
+        assert_eq!(coordinates.value((5, 30)), None);
+        Ok(())
+    }
+}
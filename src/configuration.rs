@@ -1,14 +1,71 @@
-use crate::Analysis;
+use crate::{download::download, Analysis};
+use plotters_canvas::CanvasBackend;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::time::Duration;
+use strum::IntoEnumIterator;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlCanvasElement, HtmlInputElement, KeyboardEvent};
 use yew::prelude::*;
+use yew::services::{
+    keyboard::KeyListenerHandle,
+    reader::{FileData, ReaderTask},
+    timeout::TimeoutTask,
+    KeyboardService, ReaderService, TimeoutService,
+};
 
+mod history;
+mod preview;
 pub mod settings;
-use settings::{Set, Settings};
+use history::History;
+use preview::{Preview, PREVIEW_SIZE};
+use settings::{
+    BorderConditions, FnInput, InterpolationKind, Preset, Recovered, Set, Settings, SettingsError,
+    Storage, Table, TableError, FILE_MIME, FILE_NAME,
+};
+
+/// Time without edits before the preview is updated.
+const DEBOUNCE: Duration = Duration::from_millis(300);
 
 #[derive(Debug)]
 pub enum Msg {
     SetReceiver(ComponentLink<Analysis>),
     UpdateReceiver,
     Auxiliary(Set),
+    /// The initial conditions are being edited.
+    Draft(String),
+    /// The edition of the initial conditions paused.
+    Preview,
+    ChoosePreset(ChangeData),
+    PresetParameters(ChangeData),
+    LoadFile(ChangeData),
+    FileLoaded(FileData),
+    XColumn(ChangeData),
+    YColumn(ChangeData),
+    ScenarioName(String),
+    ChooseScenario(ChangeData),
+    Scenario(ScenarioAction),
+    DownloadSettings,
+    LoadSettings(ChangeData),
+    SettingsLoaded(FileData),
+    Undo,
+    Redo,
+    KeyDown(KeyboardEvent),
+}
+
+/// Operations on the saved scenarios.
+#[derive(Debug, Clone, Copy, PartialEq, strum::Display, strum::EnumIter)]
+pub enum ScenarioAction {
+    /// Saves the current settings under the given name
+    Save,
+    /// Replaces the current settings by the chosen scenario
+    Load,
+    /// Renames the chosen scenario to the given name
+    Rename,
+    /// Copies the chosen scenario under the given name
+    Duplicate,
+    /// Deletes the chosen scenario
+    Delete,
 }
 
 #[derive(Debug)]
@@ -16,6 +73,28 @@ pub struct Configuration {
     link: ComponentLink<Self>,
     settings: Settings,
     analysis: Option<ComponentLink<Analysis>>,
+    /// Last rejected change of each field, indexed by the field identifier.
+    errors: HashMap<&'static str, SettingsError>,
+    preview: Preview,
+    /// Initial conditions being edited, but not yet set.
+    draft: Option<FnInput>,
+    draft_string: String,
+    debounce: Option<TimeoutTask>,
+    preset: Option<Preset>,
+    preset_parameters: String,
+    reader: Option<ReaderTask>,
+    /// Imported table, with its file name.
+    table: Option<(String, Table)>,
+    /// Columns of `table` used for the space and the value.
+    columns: (usize, usize),
+    /// Name for saving, renaming or duplicating scenarios.
+    scenario_name: String,
+    /// Chosen saved scenario.
+    scenario: Option<String>,
+    scenario_names: Vec<String>,
+    /// Settings before each change, to undo and redo them.
+    history: History<Settings>,
+    _key_listener: KeyListenerHandle,
 }
 
 impl Component for Configuration {
@@ -23,10 +102,36 @@ impl Component for Configuration {
     type Properties = ();
 
     fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let Recovered { settings, errors } = Settings::restore_from_url_or_storage();
+        let mut field_errors = HashMap::new();
+        if !errors.is_empty() {
+            field_errors.insert("restore", SettingsError::Recovered(errors));
+        }
+        let key_listener = KeyboardService::register_key_down(
+            &yew::utils::document(),
+            link.callback(Msg::KeyDown),
+        );
         Self {
             link,
-            settings: Settings::restore_or_default(),
+            settings,
             analysis: None,
+            errors: field_errors,
+            preview: Preview::new(NodeRef::default()),
+            draft: None,
+            draft_string: String::new(),
+            debounce: None,
+            preset: None,
+            preset_parameters: String::new(),
+            reader: None,
+            table: None,
+            columns: (0, 1),
+            scenario_name: String::new(),
+            scenario: None,
+            scenario_names: Storage::load()
+                .map(|storage| storage.scenario_names())
+                .unwrap_or_default(),
+            history: History::default(),
+            _key_listener: key_listener,
         }
     }
 
@@ -41,6 +146,9 @@ impl Component for Configuration {
                 log::trace!("Start to update receiver");
                 log::trace!("Storing settings");
                 self.settings.store().unwrap();
+                if let Err(e) = self.settings.store_in_url() {
+                    log::error!("Could not share settings in the URL: {}", e);
+                }
                 log::trace!("Updating receiver");
                 self.analysis
                     .as_mut()
@@ -50,7 +158,258 @@ impl Component for Configuration {
             }
             Msg::Auxiliary(set) => {
                 log::trace!("Changing a seeting");
-                self.settings.update(set)
+                let field = set.field();
+                let previous = self.settings.clone();
+                match self.settings.update(set) {
+                    Ok(should_render) if field == "default" => {
+                        self.record(previous);
+                        self.errors.clear();
+                        self.draft = None;
+                        should_render
+                    }
+                    Ok(should_render) => {
+                        if should_render {
+                            self.record(previous);
+                        }
+                        if ["initial_conditions", "preset", "file"].contains(&field) {
+                            self.draft = None;
+                            self.errors.remove("initial_conditions");
+                        }
+                        self.errors.remove(field).is_some() || should_render
+                    }
+                    Err(e) => {
+                        log::warn!("Rejected change of {}: {}", field, e);
+                        self.errors.insert(field, e);
+                        true
+                    }
+                }
+            }
+            Msg::Draft(s) => {
+                self.draft_string = s;
+                self.debounce = Some(TimeoutService::spawn(
+                    DEBOUNCE,
+                    self.link.callback(|_| Msg::Preview),
+                ));
+                false
+            }
+            Msg::Preview => {
+                log::trace!("Previewing {}", self.draft_string);
+                self.debounce = None;
+                match self.draft_string.parse::<FnInput>() {
+                    Ok(fn_input) => {
+                        self.draft = Some(fn_input);
+                        self.errors.remove("initial_conditions");
+                    }
+                    Err(e) => {
+                        self.errors.insert("initial_conditions", e.into());
+                    }
+                }
+                true
+            }
+            Msg::ChoosePreset(data) => {
+                if let ChangeData::Select(select_element) = data {
+                    log::trace!("Choosing preset {:?}", select_element.value());
+                    match select_element.value().parse::<Preset>() {
+                        Ok(preset) => {
+                            self.preset = Some(preset);
+                            self.preset_parameters = preset
+                                .parameters()
+                                .map(|(_, default)| default.to_string())
+                                .unwrap_or_default();
+                            self.update(Msg::Auxiliary(Set::Preset {
+                                preset,
+                                parameters: self.preset_parameters.clone(),
+                            }))
+                        }
+                        Err(e) => {
+                            log::error!("Unknown preset: {}", e);
+                            false
+                        }
+                    }
+                } else {
+                    log::error!("Tried to choose preset {:?}", data);
+                    false
+                }
+            }
+            Msg::PresetParameters(data) => match (data, self.preset) {
+                (ChangeData::Value(parameters), Some(preset)) => {
+                    log::trace!("Changing preset parameters to {}", parameters);
+                    self.preset_parameters = parameters.clone();
+                    self.update(Msg::Auxiliary(Set::Preset { preset, parameters }))
+                }
+                (data, _) => {
+                    log::error!("Tried to change preset parameters to {:?}", data);
+                    false
+                }
+            },
+            Msg::LoadFile(data) => {
+                if let ChangeData::Files(files) = data {
+                    if let Some(file) = files.get(0) {
+                        log::trace!("Reading file {}", file.name());
+                        let callback = self.link.callback(Msg::FileLoaded);
+                        match ReaderService::read_file(file, callback) {
+                            Ok(task) => self.reader = Some(task),
+                            Err(e) => log::error!("Could not read the file: {}", e),
+                        }
+                    }
+                } else {
+                    log::error!("Tried to load file {:?}", data);
+                }
+                false
+            }
+            Msg::FileLoaded(data) => {
+                log::trace!("Parsing file {}", data.name);
+                self.reader = None;
+                let table = String::from_utf8(data.content)
+                    .map_err(|_| TableError::Encoding)
+                    .and_then(|content| Table::parse(&content));
+                match table {
+                    Ok(table) => {
+                        self.table = Some((data.name, table));
+                        self.columns = (0, 1);
+                        self.import_table()
+                    }
+                    Err(e) => {
+                        self.table = None;
+                        self.errors.insert("file", e.into());
+                        true
+                    }
+                }
+            }
+            Msg::XColumn(ChangeData::Select(select_element)) => {
+                match usize::try_from(select_element.selected_index()) {
+                    Ok(index) => {
+                        self.columns.0 = index;
+                        self.import_table()
+                    }
+                    Err(_) => false,
+                }
+            }
+            Msg::YColumn(ChangeData::Select(select_element)) => {
+                match usize::try_from(select_element.selected_index()) {
+                    Ok(index) => {
+                        self.columns.1 = index;
+                        self.import_table()
+                    }
+                    Err(_) => false,
+                }
+            }
+            Msg::XColumn(data) | Msg::YColumn(data) => {
+                log::error!("Tried to choose column {:?}", data);
+                false
+            }
+            Msg::ScenarioName(name) => {
+                self.scenario_name = name;
+                false
+            }
+            Msg::ChooseScenario(data) => {
+                if let ChangeData::Select(select_element) = data {
+                    log::trace!("Choosing scenario {:?}", select_element.value());
+                    self.scenario = Some(select_element.value());
+                    true
+                } else {
+                    log::error!("Tried to choose scenario {:?}", data);
+                    false
+                }
+            }
+            Msg::Scenario(action) => {
+                log::trace!("Scenario action: {}", action);
+                match self.scenario_action(action) {
+                    Ok(()) => {
+                        self.errors.remove("scenarios");
+                    }
+                    Err(e) => {
+                        log::warn!("Could not {} scenario: {}", action, e);
+                        self.errors.insert("scenarios", SettingsError::Storage(e));
+                    }
+                }
+                true
+            }
+            Msg::DownloadSettings => {
+                log::trace!("Downloading settings");
+                match self
+                    .settings
+                    .to_file()
+                    .and_then(|content| download(FILE_NAME, FILE_MIME, &content))
+                {
+                    Ok(()) => self.errors.remove("settings_file").is_some(),
+                    Err(e) => {
+                        log::error!("Could not download the settings: {}", e);
+                        self.errors
+                            .insert("settings_file", SettingsError::Storage(e));
+                        true
+                    }
+                }
+            }
+            Msg::LoadSettings(data) => {
+                if let ChangeData::Files(files) = data {
+                    if let Some(file) = files.get(0) {
+                        log::trace!("Reading settings file {}", file.name());
+                        let callback = self.link.callback(Msg::SettingsLoaded);
+                        match ReaderService::read_file(file, callback) {
+                            Ok(task) => self.reader = Some(task),
+                            Err(e) => log::error!("Could not read the file: {}", e),
+                        }
+                    }
+                } else {
+                    log::error!("Tried to load settings file {:?}", data);
+                }
+                false
+            }
+            Msg::SettingsLoaded(data) => {
+                log::trace!("Parsing settings file {}", data.name);
+                self.reader = None;
+                match Settings::from_file(&data.content) {
+                    Ok(settings) => {
+                        let previous = std::mem::replace(&mut self.settings, settings);
+                        self.record(previous);
+                        self.errors.clear();
+                        self.draft = None;
+                    }
+                    Err(e) => {
+                        log::warn!("Rejected settings file {}: {}", data.name, e);
+                        self.errors.insert("settings_file", e);
+                    }
+                }
+                true
+            }
+            Msg::Undo => match self.history.undo(self.settings.clone()) {
+                Some(settings) => {
+                    log::trace!("Undoing the last change");
+                    self.settings = settings;
+                    self.errors.clear();
+                    self.draft = None;
+                    true
+                }
+                None => false,
+            },
+            Msg::Redo => match self.history.redo(self.settings.clone()) {
+                Some(settings) => {
+                    log::trace!("Redoing the last undone change");
+                    self.settings = settings;
+                    self.errors.clear();
+                    self.draft = None;
+                    true
+                }
+                None => false,
+            },
+            Msg::KeyDown(event) => {
+                // Text fields keep their own undo
+                let editing = event
+                    .target()
+                    .and_then(|target| target.dyn_into::<HtmlInputElement>().ok())
+                    .is_some_and(|input| ["text", "number"].contains(&input.type_().as_str()));
+                if !(event.ctrl_key() || event.meta_key()) || editing {
+                    return false;
+                }
+                let msg = match event.key().to_lowercase().as_str() {
+                    "z" if event.shift_key() => Msg::Redo,
+                    "z" => Msg::Undo,
+                    "y" => Msg::Redo,
+                    _ => return false,
+                };
+                event.prevent_default();
+                self.update(msg)
             }
         }
     }
@@ -64,7 +423,35 @@ impl Component for Configuration {
             <p>
                 { "Configuration" }
                 <br/>
+                { self.view_error("restore") }
 
+                <div>
+                    { "Presets" }
+                    <select
+                        name="preset"
+                        id="preset"
+                        onchange=self.link.callback(Msg::ChoosePreset)
+                    >
+                        <option value="" selected=self.preset.is_none() disabled=true>{ "Choose one" }</option>
+                        { for Preset::iter().map(|preset| html! {
+                            <option value=preset.to_string() selected=self.preset == Some(preset)>{ preset.to_string() }</option>
+                        }) }
+                    </select>
+                    { self.view_preset_parameters() }
+                    { self.view_error("preset") }
+                </div>
+                <div>
+                    { "Import points" }
+                    <input
+                        type="file"
+                        id="file"
+                        name="file"
+                        accept=".csv,.tsv,.txt"
+                        onchange=self.link.callback(Msg::LoadFile)
+                    />
+                    { self.view_columns() }
+                    { self.view_error("file") }
+                </div>
                 <div>
                     { "Initial conditions" }
                     <input
@@ -72,15 +459,20 @@ impl Component for Configuration {
                         id="initial_conditions"
                         name="initial_conditions"
                         value=self.settings.initial_conditions.string.clone()
+                        oninput=self.link.callback(|x: InputData| Msg::Draft(x.value))
                         onchange=self.link.callback(move |f| Msg::Auxiliary(Set::InitialConditions(f)))
                     />
                     <div class="tooltip">{ "Available fomats?" }
-                        <span class="tooltiptext">{ "analytical: sin({x})\npoints: [(0, 2), (1, 3.5)]" }</span>
+                        <span class="tooltiptext">{ "analytical: sin({x})\npiecewise: (-0.5, 0.5): 1; else: 0\npoints: [(0, 2), (1, 3.5)]" }</span>
                     </div>
+                    { self.view_error("initial_conditions") }
+                    <br/>
+                    <canvas ref={self.preview.canvas_ref.clone()} />
                 </div>
                 <div>
                     { "Time step" }
                     <input type="number" id="time_step" name="time_step" min="0" max="100" value=self.settings.kernel.time_step().to_string() onchange=self.link.callback(|x| Msg::Auxiliary(Set::TimeStep(x)))/>
+                    { self.view_error("time_step") }
                 </div>
                 <div>
                     { "Border conditions" }
@@ -89,19 +481,225 @@ impl Component for Configuration {
                         id="border_conditions"
                         onchange=self.link.callback(|x| Msg::Auxiliary(Set::BorderConditions(x)))
                     >
-                        <option value="Fixed">{ "Fixed" }</option>
-                        <option value="Periodic">{ "Periodic" }</option>
+                        <option value="Fixed" selected=self.settings.border_conditions == BorderConditions::Fixed>{ "Fixed" }</option>
+                        <option value="Periodic" selected=self.settings.border_conditions == BorderConditions::Periodic>{ "Periodic" }</option>
                     </select>
+                    { self.view_error("border_conditions") }
+                </div>
+                <div>
+                    { "Interpolation" }
+                    <select
+                        name="interpolation"
+                        id="interpolation"
+                        onchange=self.link.callback(|x| Msg::Auxiliary(Set::Interpolation(x)))
+                    >
+                        { for InterpolationKind::iter().map(|interpolation| html! {
+                            <option value=interpolation.to_string() selected=self.settings.interpolation == interpolation>{ interpolation.to_string() }</option>
+                        }) }
+                    </select>
+                    { self.view_error("interpolation") }
                 </div>
                 <div>
                     { "Quality" }
                     <input type="range" id="quality" name="quality" min="2" max="100" value=self.settings.quality.to_string() class="slider" onchange=self.link.callback(|x| Msg::Auxiliary(Set::Quality(x)))/>
+                    { self.view_error("quality") }
+                </div>
+                <div>
+                    { "Scenarios" }
+                    <select
+                        name="scenarios"
+                        id="scenarios"
+                        onchange=self.link.callback(Msg::ChooseScenario)
+                    >
+                        <option value="" selected=self.scenario.is_none() disabled=true>{ "Choose one" }</option>
+                        { for self.scenario_names.iter().map(|name| html! {
+                            <option value=name.clone() selected=self.scenario.as_ref() == Some(name)>{ name }</option>
+                        }) }
+                    </select>
+                    <input
+                        type="text"
+                        id="scenario_name"
+                        name="scenario_name"
+                        placeholder="Name"
+                        value=self.scenario_name.clone()
+                        oninput=self.link.callback(|x: InputData| Msg::ScenarioName(x.value))
+                    />
+                    { for ScenarioAction::iter().map(|action| html! {
+                        <button type="button" onclick=self.link.callback(move |_| Msg::Scenario(action))>{ action.to_string() }</button>
+                    }) }
+                    { self.view_error("scenarios") }
+                </div>
+                <div>
+                    <button type="button" id="download_settings" name="download_settings" onclick=self.link.callback(|_| Msg::DownloadSettings)>{ "Download settings" }</button>
+                    { "Load settings" }
+                    <input
+                        type="file"
+                        id="settings_file"
+                        name="settings_file"
+                        accept=".ron,.txt"
+                        onchange=self.link.callback(Msg::LoadSettings)
+                    />
+                    { self.view_error("settings_file") }
                 </div>
                 <div>
                     <button type="button" id="update_receiver" name="update_receiver" onclick=self.link.callback(|_| Msg::UpdateReceiver)>{ "Update" }</button>
                     <button type="button" id="default" name="default" onclick=self.link.callback(|_| Msg::Auxiliary(Set::Default))>{ "Default" }</button>
+                    <button type="button" id="undo" name="undo" title="Ctrl+Z" disabled=!self.history.can_undo() onclick=self.link.callback(|_| Msg::Undo)>{ "Undo" }</button>
+                    <button type="button" id="redo" name="redo" title="Ctrl+Shift+Z" disabled=!self.history.can_redo() onclick=self.link.callback(|_| Msg::Redo)>{ "Redo" }</button>
                 </div>
             </p>
         }
     }
+
+    fn rendered(&mut self, first_render: bool) {
+        let preview_canvas: HtmlCanvasElement =
+            self.preview.canvas_ref.cast::<HtmlCanvasElement>().unwrap();
+        if first_render {
+            log::trace!("First render of Configuration");
+            preview_canvas.set_width(PREVIEW_SIZE.0);
+            preview_canvas.set_height(PREVIEW_SIZE.1);
+        }
+        let preview_backend: CanvasBackend =
+            CanvasBackend::with_canvas_object(preview_canvas).unwrap();
+        let mut fn_input = self
+            .draft
+            .as_ref()
+            .unwrap_or(&self.settings.initial_conditions)
+            .clone();
+        fn_input.set_interpolation(self.settings.interpolation);
+        if let Err(e) = self.preview.plot(
+            preview_backend,
+            self.settings.domain,
+            self.settings.quality,
+            &fn_input,
+        ) {
+            log::error!("Could not plot the preview: {}", e);
+        }
+    }
+}
+
+impl Configuration {
+    /// Records the settings before a change in the history, unless they did not change.
+    fn record(&mut self, previous: Settings) {
+        if !previous.same(&self.settings) {
+            self.history.push(previous);
+        }
+    }
+
+    fn scenario_action(&mut self, action: ScenarioAction) -> anyhow::Result<()> {
+        let chosen = || {
+            self.scenario
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("Choose a scenario first"))
+        };
+        let name = self.scenario_name.trim().to_string();
+        match action {
+            ScenarioAction::Save => {
+                Storage::modify(|storage| storage.save_scenario(&name, &self.settings))?;
+                self.scenario = Some(name);
+            }
+            ScenarioAction::Load => {
+                let Recovered { settings, errors } = Storage::load()?.load_scenario(&chosen()?)?;
+                let previous = std::mem::replace(&mut self.settings, settings);
+                self.record(previous);
+                self.errors.clear();
+                self.draft = None;
+                if !errors.is_empty() {
+                    return Err(SettingsError::Recovered(errors).into());
+                }
+            }
+            ScenarioAction::Rename => {
+                let chosen = chosen()?;
+                Storage::modify(|storage| storage.rename_scenario(&chosen, &name))?;
+                self.scenario = Some(name);
+            }
+            ScenarioAction::Duplicate => {
+                let chosen = chosen()?;
+                Storage::modify(|storage| storage.duplicate_scenario(&chosen, &name))?;
+                self.scenario = Some(name);
+            }
+            ScenarioAction::Delete => {
+                let chosen = chosen()?;
+                Storage::modify(|storage| storage.delete_scenario(&chosen))?;
+                self.scenario = None;
+            }
+        }
+        self.scenario_names = Storage::load()?.scenario_names();
+        Ok(())
+    }
+
+    /// Uses the chosen columns of the imported table as initial conditions.
+    fn import_table(&mut self) -> ShouldRender {
+        match &self.table {
+            Some((name, table)) => {
+                let points = table.points(self.columns.0, self.columns.1);
+                let name = name.clone();
+                self.update(Msg::Auxiliary(Set::File { name, points }))
+            }
+            None => false,
+        }
+    }
+
+    /// Displays the choice of columns of the imported table, if any.
+    fn view_columns(&self) -> Html {
+        let options = |selected: usize| -> Html {
+            match &self.table {
+                Some((_, table)) => table
+                    .headers
+                    .iter()
+                    .enumerate()
+                    .map(|(i, header)| {
+                        html! {
+                            <option value=i.to_string() selected=i == selected>{ header }</option>
+                        }
+                    })
+                    .collect(),
+                None => html! {},
+            }
+        };
+        match &self.table {
+            Some(_) => html! {
+                <>
+                    { "x" }
+                    <select name="x_column" id="x_column" onchange=self.link.callback(Msg::XColumn)>
+                        { options(self.columns.0) }
+                    </select>
+                    { "u" }
+                    <select name="y_column" id="y_column" onchange=self.link.callback(Msg::YColumn)>
+                        { options(self.columns.1) }
+                    </select>
+                </>
+            },
+            None => html! {},
+        }
+    }
+
+    /// Displays an input for the parameters of the chosen preset, if it has any.
+    fn view_preset_parameters(&self) -> Html {
+        match self.preset.and_then(|preset| preset.parameters()) {
+            Some((name, _)) => html! {
+                <>
+                    { name }
+                    <input
+                        type="text"
+                        id="preset_parameters"
+                        name="preset_parameters"
+                        value=self.preset_parameters.clone()
+                        onchange=self.link.callback(Msg::PresetParameters)
+                    />
+                </>
+            },
+            None => html! {},
+        }
+    }
+
+    /// Displays the reason why the last change of `field` was rejected, if any.
+    fn view_error(&self, field: &str) -> Html {
+        match self.errors.get(field) {
+            Some(e) => html! {
+                <span class="error">{ e.to_string() }</span>
+            },
+            None => html! {},
+        }
+    }
 }
@@ -0,0 +1,79 @@
+//! Undo and redo of changes
+
+/// Number of changes that can be undone.
+const LIMIT: usize = 100;
+
+/// Previous and undone states.
+#[derive(Debug, Clone)]
+pub struct History<T> {
+    undo: Vec<T>,
+    redo: Vec<T>,
+}
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        Self {
+            undo: vec![],
+            redo: vec![],
+        }
+    }
+}
+
+impl<T> History<T> {
+    /// Records the state before a change, which can no longer be redone.
+    pub fn push(&mut self, previous: T) {
+        if self.undo.len() == LIMIT {
+            self.undo.remove(0);
+        }
+        self.undo.push(previous);
+        self.redo.clear();
+    }
+
+    /// Returns the state before the last change, if any, keeping `current` to be redone.
+    pub fn undo(&mut self, current: T) -> Option<T> {
+        let previous = self.undo.pop()?;
+        self.redo.push(current);
+        Some(previous)
+    }
+
+    /// Returns the state before the last undo, if any, keeping `current` to be undone.
+    pub fn redo(&mut self, current: T) -> Option<T> {
+        let next = self.redo.pop()?;
+        self.undo.push(current);
+        Some(next)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_redo() {
+        let mut history = History::default();
+        assert_eq!(history.undo(0), None);
+        history.push(0);
+        history.push(1);
+        assert_eq!(history.undo(2), Some(1));
+        assert_eq!(history.undo(1), Some(0));
+        assert!(!history.can_undo());
+        assert_eq!(history.redo(0), Some(1));
+        history.push(1);
+        assert!(!history.can_redo());
+        assert_eq!(history.undo(3), Some(1));
+
+        for i in 0..2 * LIMIT {
+            history.push(i);
+        }
+        assert_eq!(history.undo.len(), LIMIT);
+        assert_eq!(history.undo[0], LIMIT);
+    }
+}
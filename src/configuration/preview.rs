@@ -0,0 +1,83 @@
+use itertools::Itertools;
+use plotters::prelude::*;
+use plotters_canvas::CanvasBackend;
+use yew::prelude::*;
+
+use crate::configuration::settings::FnInput;
+
+/// Size of the preview canvas.
+pub const PREVIEW_SIZE: (u32, u32) = (240, 120);
+
+/// Preview of an initial condition
+#[derive(Debug)]
+pub struct Preview {
+    pub canvas_ref: NodeRef,
+}
+
+impl Preview {
+    pub fn new(canvas_ref: NodeRef) -> Self {
+        Self { canvas_ref }
+    }
+
+    /// Plots `fn_input` over `domain` sampled at `quality` points.
+    ///
+    /// Regions where the function is not a real number (NaN or infinite) are highlighted in red.
+    pub fn plot(
+        &self,
+        backend: CanvasBackend,
+        domain: (f64, f64),
+        quality: usize,
+        fn_input: &FnInput,
+    ) -> anyhow::Result<()> {
+        // Pre-computations
+        let grid: Vec<f64> = itertools_num::linspace(domain.0, domain.1, quality).collect();
+        let values: Vec<f64> = grid.iter().map(|x| fn_input.eval(*x)).collect();
+        let (min, max) = values
+            .iter()
+            .filter(|v| v.is_finite())
+            .copied()
+            .minmax()
+            .into_option()
+            .unwrap_or((-1., 1.));
+        let delta = (max - min).max(f64::EPSILON);
+        let y_range = (min - delta / 10.)..(max + delta / 10.);
+
+        // Plottings
+        let root = backend.into_drawing_area();
+        root.fill(&WHITE)?;
+
+        let mut chart = ChartBuilder::on(&root)
+            .set_label_area_size(LabelAreaPosition::Bottom, 20)
+            .set_label_area_size(LabelAreaPosition::Left, 30)
+            .build_cartesian_2d(domain.0..domain.1, y_range.clone())?;
+        chart.configure_mesh().x_labels(5).y_labels(3).draw()?;
+
+        // Non-real regions
+        let step = (domain.1 - domain.0) / (quality as f64 - 1.);
+        chart.draw_series(
+            grid.iter()
+                .zip(&values)
+                .filter(|(_, v)| !v.is_finite())
+                .map(|(x, _)| {
+                    Rectangle::new(
+                        [
+                            ((x - step / 2.).max(domain.0), y_range.start),
+                            ((x + step / 2.).min(domain.1), y_range.end),
+                        ],
+                        RED.mix(0.3).filled(),
+                    )
+                }),
+        )?;
+
+        // Function, split where it is not real
+        for segment in &grid.iter().zip(&values).group_by(|(_, v)| v.is_finite()) {
+            if let (true, points) = segment {
+                chart.draw_series(LineSeries::new(points.map(|(x, v)| (*x, *v)), &BLACK))?;
+            }
+        }
+
+        root.present()?;
+
+        Ok(())
+    }
+}
@@ -1,14 +1,29 @@
 use crate::kernel::Kernel;
-use serde::{Deserialize, Serialize};
-use splines::{Interpolation, Key, Spline};
+use serde::Serialize;
+use splines::Spline;
 use yew::prelude::*;
 
 mod border_conditions;
+mod error;
+mod file;
 mod fn_input;
+mod interpolation;
+mod preset;
+mod schema;
 mod storage;
+mod url;
 
 pub use border_conditions::BorderConditions;
-pub use fn_input::{kind::FnInputKind, FnInput};
+pub use error::SettingsError;
+pub use file::{FILE_MIME, FILE_NAME};
+pub use fn_input::{
+    table::{Table, TableError},
+    FnInput,
+};
+pub use interpolation::InterpolationKind;
+pub use preset::Preset;
+pub use schema::Recovered;
+pub use storage::Storage;
 
 #[derive(Debug)]
 pub enum Set {
@@ -17,43 +32,81 @@ pub enum Set {
     Quality(ChangeData),
     Default,
     TimeStep(ChangeData),
+    Preset {
+        preset: Preset,
+        parameters: String,
+    },
+    Interpolation(ChangeData),
+    /// Points imported from a file
+    File {
+        name: String,
+        points: Vec<(f64, f64)>,
+    },
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+/// Settings of the solver.
+///
+/// They are read with `Settings::from_ron`, which migrates older versions.
+#[derive(Clone, Debug, Serialize)]
 pub struct Settings {
+    /// Version of the schema, see `schema`.
+    pub version: u32,
     pub initial_conditions: FnInput,
     pub domain: (f64, f64), // TODO
     pub border_conditions: BorderConditions,
     pub quality: usize,
     pub kernel: Kernel,
-    pub canvas_size: (u32, u32),
+    /// Interpolation for collections of points and for the solution at each time.
+    pub interpolation: InterpolationKind,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
+            version: schema::SCHEMA_VERSION,
             initial_conditions: FnInput::default(),
             domain: (-1., 1.),
             border_conditions: BorderConditions::default(),
             quality: 100,
             kernel: Kernel::default(),
-            canvas_size: (360, 360),
+            interpolation: InterpolationKind::default(),
+        }
+    }
+}
+
+impl Set {
+    /// Identifier of the field this change refers to.
+    pub fn field(&self) -> &'static str {
+        match self {
+            Set::InitialConditions(_) => "initial_conditions",
+            Set::BorderConditions(_) => "border_conditions",
+            Set::Quality(_) => "quality",
+            Set::Default => "default",
+            Set::TimeStep(_) => "time_step",
+            Set::Preset { .. } => "preset",
+            Set::Interpolation(_) => "interpolation",
+            Set::File { .. } => "file",
         }
     }
 }
 
 impl Settings {
-    pub fn update(&mut self, set: Set) -> ShouldRender {
+    /// Applies a change to the settings.
+    ///
+    /// # Errors
+    ///
+    /// If the proposed value is not valid, in which case the previous value is kept.
+    pub fn update(&mut self, set: Set) -> Result<ShouldRender, SettingsError> {
         match set {
             Set::InitialConditions(data) => {
                 if let ChangeData::Value(s) = data {
                     log::trace!("Trying to change initial conditions to {}", s);
-                    let proposal = s.parse().unwrap();
+                    let proposal = s.parse()?;
                     self.initial_conditions = proposal;
-                    true
+                    Ok(true)
                 } else {
                     log::error!("Tried to change initial conditions to {:?}", data);
-                    false
+                    Ok(false)
                 }
             }
             Set::BorderConditions(data) => {
@@ -62,49 +115,90 @@ impl Settings {
                         "Trying to change border conditions to {:?}",
                         select_element.value()
                     );
-                    let proposal = select_element.value().parse().unwrap();
+                    let proposal = select_element.value().parse()?;
                     self.border_conditions = proposal;
-                    true
+                    Ok(true)
                 } else {
                     log::error!("Tried to change border conditions to {:?}", data);
-                    false
+                    Ok(false)
                 }
             }
             Set::Quality(data) => {
                 if let ChangeData::Value(x) = data {
                     log::trace!("Trying to change quality to {}", x);
-                    let proposal: usize = x.parse().unwrap();
+                    let proposal: usize = x.parse()?;
+                    if proposal < 2 {
+                        return Err(SettingsError::QualityTooLow(proposal));
+                    }
                     self.quality = proposal;
-                    true
+                    Ok(true)
                 } else {
                     log::error!("Tried to change quality to {:?}", data);
-                    false
+                    Ok(false)
                 }
             }
             Set::Default => {
-                *self = Settings::remove_and_default();
-                true
+                *self = Settings::default();
+                Ok(true)
             }
             Set::TimeStep(data) => {
                 if let ChangeData::Value(x) = data {
                     log::trace!("Trying to change time step to {}", x);
-                    let proposal: f64 = x.parse().unwrap();
+                    let proposal: f64 = x.parse()?;
+                    if proposal <= 0. || !proposal.is_finite() {
+                        return Err(SettingsError::NonPositiveTimeStep(proposal));
+                    }
                     self.kernel.set_time_step(proposal);
-                    true
+                    Ok(true)
                 } else {
                     log::error!("Tried to change time step to {:?}", data);
-                    false
+                    Ok(false)
+                }
+            }
+            Set::Preset { preset, parameters } => {
+                log::trace!("Trying to use preset {} with {}", preset, parameters);
+                let proposal = preset
+                    .fn_input_string(self.domain, self.quality, &parameters)?
+                    .parse()?;
+                self.initial_conditions = proposal;
+                Ok(true)
+            }
+            Set::Interpolation(data) => {
+                if let ChangeData::Select(select_element) = data {
+                    log::trace!(
+                        "Trying to change interpolation to {:?}",
+                        select_element.value()
+                    );
+                    let proposal = select_element.value().parse()?;
+                    self.interpolation = proposal;
+                    Ok(true)
+                } else {
+                    log::error!("Tried to change interpolation to {:?}", data);
+                    Ok(false)
                 }
             }
+            Set::File { name, points } => {
+                log::trace!("Importing {} points from {}", points.len(), name);
+                self.initial_conditions = FnInput::from_file(name, points)?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Whether both settings are the same, as they are stored.
+    pub fn same(&self, other: &Settings) -> bool {
+        match (ron::ser::to_string(self), ron::ser::to_string(other)) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
         }
     }
 
     pub fn compute_initial_spline(&self) -> Spline<f64, f64> {
+        let mut initial_conditions = self.initial_conditions.clone();
+        initial_conditions.set_interpolation(self.interpolation);
         let grid = itertools_num::linspace(self.domain.0, self.domain.1, self.quality);
 
-        Spline::from_iter(grid.map(|x| {
-            let y = self.initial_conditions.eval(x);
-            Key::new(x, y, Interpolation::Cosine)
-        }))
+        self.interpolation
+            .spline(grid.map(|x| (x, initial_conditions.eval(x))))
     }
 }
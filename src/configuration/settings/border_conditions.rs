@@ -1,12 +1,7 @@
 use serde::{Deserialize, Serialize};
-#[derive(Debug, Clone, Deserialize, Serialize, strum::EnumString)]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, strum::EnumString)]
 pub enum BorderConditions {
+    #[default]
     Fixed,
     Periodic,
 }
-
-impl Default for BorderConditions {
-    fn default() -> Self {
-        BorderConditions::Fixed
-    }
-}
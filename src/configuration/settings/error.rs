@@ -0,0 +1,46 @@
+//! Errors when changing settings
+
+use core::num::{ParseFloatError, ParseIntError};
+use thiserror::Error;
+
+use itertools::Itertools;
+
+use crate::configuration::settings::fn_input::{kind::FormatError, table::TableError};
+use crate::configuration::settings::schema::{FieldError, SchemaError};
+
+/// Reasons why a proposed setting was rejected.
+///
+/// When a change is rejected, the previous valid value is kept.
+#[derive(Error, Debug)]
+pub enum SettingsError {
+    #[error("Invalid initial conditions. {0}")]
+    InitialConditions(#[from] FormatError),
+    #[error("Invalid option: {0}")]
+    Choice(#[from] strum::ParseError),
+    #[error("Quality must be an integer: {0}")]
+    Quality(#[from] ParseIntError),
+    #[error("Quality must be at least 2, got {0}")]
+    QualityTooLow(usize),
+    #[error("Time step must be a number: {0}")]
+    TimeStep(#[from] ParseFloatError),
+    #[error("Time step must be positive, got {0}")]
+    NonPositiveTimeStep(f64),
+    #[error("The inverse standard deviation of the kernel must be positive, got {0}")]
+    NonPositiveStdDevInv(f64),
+    #[error("Domain must be a finite interval, got ({0}, {1})")]
+    Domain(f64, f64),
+    #[error("Invalid preset parameters `{0}`")]
+    PresetParameters(String),
+    #[error("Invalid file. {0}")]
+    Table(#[from] TableError),
+    #[error("{0}")]
+    Storage(anyhow::Error),
+    #[error("Invalid settings. {0}")]
+    Schema(#[from] SchemaError),
+    #[error("Some settings were reset: {}", .0.iter().join("; "))]
+    Recovered(Vec<FieldError>),
+    #[error("The settings file is not valid text")]
+    Encoding,
+    #[error("Invalid settings file: {}", .0.iter().join("; "))]
+    InvalidFields(Vec<FieldError>),
+}
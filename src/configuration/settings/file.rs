@@ -0,0 +1,112 @@
+//! Settings files
+//!
+//! Files hold the pretty RON written by the `Serialize` implementation of the settings, so
+//! that a scenario can be shared across machines or used by other runners. Unlike storage and
+//! URLs, a file is not recovered field by field: if any field is invalid, the file is rejected.
+
+use crate::configuration::settings::{Recovered, Settings, SettingsError};
+
+pub const FILE_NAME: &str = "heat_eq_settings.ron";
+pub const FILE_MIME: &str = "text/plain";
+
+impl Settings {
+    pub fn to_file(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(ron::ser::to_string_pretty(self, Default::default())?.into_bytes())
+    }
+
+    /// Reads and validates the content of a settings file.
+    ///
+    /// # Errors
+    ///
+    /// If the file is not a RON structure, if any field can not be read or if the values are
+    /// not valid settings.
+    pub fn from_file(content: &[u8]) -> Result<Self, SettingsError> {
+        let ron = std::str::from_utf8(content).map_err(|_| SettingsError::Encoding)?;
+        let Recovered { settings, errors } = Settings::read_ron(ron)?;
+        if !errors.is_empty() {
+            return Err(SettingsError::InvalidFields(errors));
+        }
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Checks the values that can not be checked by their type.
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        self.validate_quality()?;
+        self.validate_kernel()?;
+        self.validate_domain()
+    }
+
+    pub(super) fn validate_quality(&self) -> Result<(), SettingsError> {
+        if self.quality < 2 {
+            return Err(SettingsError::QualityTooLow(self.quality));
+        }
+        Ok(())
+    }
+
+    pub(super) fn validate_kernel(&self) -> Result<(), SettingsError> {
+        let time_step = self.kernel.time_step();
+        if time_step <= 0. || !time_step.is_finite() {
+            return Err(SettingsError::NonPositiveTimeStep(time_step));
+        }
+        let std_dev_inv = self.kernel.std_dev_inv();
+        if std_dev_inv <= 0. || !std_dev_inv.is_finite() {
+            return Err(SettingsError::NonPositiveStdDevInv(std_dev_inv));
+        }
+        Ok(())
+    }
+
+    pub(super) fn validate_domain(&self) -> Result<(), SettingsError> {
+        let (start, end) = self.domain;
+        if !(start.is_finite() && end.is_finite() && start < end) {
+            return Err(SettingsError::Domain(start, end));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file() -> anyhow::Result<()> {
+        let settings = Settings {
+            quality: 42,
+            initial_conditions: "(-0.5, 0.5): 1; else: 0".parse()?,
+            ..Default::default()
+        };
+        let other_settings = Settings::from_file(&settings.to_file()?)?;
+        assert_eq!(other_settings.quality, 42);
+        assert_eq!(
+            other_settings.initial_conditions.string,
+            settings.initial_conditions.string
+        );
+
+        assert!(matches!(
+            Settings::from_file(&[0xff, 0xfe]),
+            Err(SettingsError::Encoding)
+        ));
+        assert!(matches!(
+            Settings::from_file(b"not settings"),
+            Err(SettingsError::Schema(_))
+        ));
+        assert!(matches!(
+            Settings::from_file(b"(quality: \"high\")"),
+            Err(SettingsError::InvalidFields(_))
+        ));
+        assert!(matches!(
+            Settings::from_file(b"(quality: 1)"),
+            Err(SettingsError::QualityTooLow(1))
+        ));
+        assert!(matches!(
+            Settings::from_file(b"(domain: (1.0, -1.0))"),
+            Err(SettingsError::Domain(..))
+        ));
+        assert!(matches!(
+            Settings::from_file(b"(kernel: Heat(time_step: 1.0, std_dev_inv: NaN))"),
+            Err(SettingsError::NonPositiveStdDevInv(_))
+        ));
+        Ok(())
+    }
+}
@@ -1,22 +1,31 @@
 use core::str::FromStr;
 
-use serde::{ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+use serde::{de::Error, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
 
 pub mod kind;
-use kind::FnInputKind;
+pub mod table;
+use kind::{eval_expression, FnInputKind};
+use table::TableError;
+
+use crate::configuration::settings::InterpolationKind;
 
 #[derive(Debug, Clone)]
 pub struct FnInput {
     pub string: String,
     pub kind: FnInputKind,
+    /// Name of the file the points were imported from, if any.
+    ///
+    /// In this case, `string` is only a description and the points are stored instead.
+    pub file: Option<String>,
 }
 
 impl Default for FnInput {
     fn default() -> Self {
         let string = "sin({x})".to_string();
         FnInput {
-            string: string,
+            string,
             kind: FnInputKind::default(),
+            file: None,
         }
     }
 }
@@ -33,11 +42,36 @@ impl FnInput {
     //     self.string = s;
     //     self
     // }
+    /// Collection of points imported from a file.
+    ///
+    /// # Errors
+    ///
+    /// If there are no points, as there would be nothing to interpolate.
+    pub fn from_file(file: String, points: Vec<(f64, f64)>) -> Result<Self, TableError> {
+        if points.is_empty() {
+            return Err(TableError::Empty);
+        }
+        Ok(FnInput {
+            string: format!("{} ({} points)", file, points.len()),
+            kind: FnInputKind::Points {
+                spline: InterpolationKind::default().spline(points),
+            },
+            file: Some(file),
+        })
+    }
+    /// Changes the interpolation between points, if the function is a collection of points.
+    pub fn set_interpolation(&mut self, interpolation: InterpolationKind) -> &mut Self {
+        if let FnInputKind::Points { spline } = &mut self.kind {
+            *spline = interpolation.spline(spline.keys().iter().map(|k| (k.t, k.value)));
+        }
+        self
+    }
     /// Evaluates the function at a given value.
     pub fn eval(&self, value: f64) -> f64 {
         match &self.kind {
-            FnInputKind::Analytical { expression } => expression.eval(&[value]).unwrap(),
+            FnInputKind::Analytical { expression } => eval_expression(expression, value),
             FnInputKind::Points { spline } => spline.clamped_sample(value).unwrap(),
+            FnInputKind::Piecewise { piecewise } => piecewise.eval(value),
         }
     }
 }
@@ -47,8 +81,20 @@ impl Serialize for FnInput {
     where
         S: Serializer,
     {
-        let mut s = serializer.serialize_struct("FnInput", 2)?;
+        let mut s = serializer.serialize_struct("FnInput", 3)?;
         s.serialize_field("string", &self.string)?;
+        match (&self.file, &self.kind) {
+            (Some(file), FnInputKind::Points { spline }) => {
+                let points: Vec<(f64, f64)> =
+                    spline.keys().iter().map(|k| (k.t, k.value)).collect();
+                s.serialize_field("file", &Some(file))?;
+                s.serialize_field("points", &Some(points))?;
+            }
+            _ => {
+                s.skip_field("file")?;
+                s.skip_field("points")?;
+            }
+        }
         s.end()
     }
 }
@@ -61,12 +107,20 @@ impl<'de> Deserialize<'de> for FnInput {
         #[derive(Debug, Clone, Deserialize)]
         pub struct __FnInput {
             pub string: String,
+            #[serde(default)]
+            pub file: Option<String>,
+            #[serde(default)]
+            pub points: Option<Vec<(f64, f64)>>,
         }
         let __fn_input = __FnInput::deserialize(deserializer)?;
-        let kind = FnInputKind::from_str(&__fn_input.string).unwrap();
+        if let (Some(file), Some(points)) = (__fn_input.file, __fn_input.points) {
+            return FnInput::from_file(file, points).map_err(D::Error::custom);
+        }
+        let kind = FnInputKind::from_str(&__fn_input.string).map_err(D::Error::custom)?;
         Ok(FnInput {
             string: __fn_input.string,
             kind,
+            file: None,
         })
     }
 }
@@ -78,6 +132,7 @@ impl FromStr for FnInput {
         Ok(FnInput {
             string: s.to_string(),
             kind,
+            file: None,
         })
     }
 }
@@ -94,4 +149,66 @@ mod tests {
         assert_eq!(fn_input.string, other_fn_input.string);
         Ok(())
     }
+
+    #[test]
+    fn serde_file() -> anyhow::Result<()> {
+        let fn_input = FnInput::from_file("data.csv".to_string(), vec![(0., 1.), (1., 3.5)])?;
+        let string: String = ron::ser::to_string(&fn_input)?;
+        let other_fn_input: FnInput = ron::de::from_str(&string)?;
+        assert_eq!(other_fn_input.file.as_deref(), Some("data.csv"));
+        assert_eq!(other_fn_input.eval(1.), 3.5);
+
+        assert!(FnInput::from_file("data.csv".to_string(), vec![]).is_err());
+        let empty = r#"(string:"data.csv (0 points)",file:Some("data.csv"),points:Some([]))"#;
+        assert!(ron::de::from_str::<FnInput>(empty).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn from_str() {
+        assert!("sin({x})".parse::<FnInput>().is_ok());
+        assert!("[(0, 2), (1, 3.5)]".parse::<FnInput>().is_ok());
+        assert!("sin({x}".parse::<FnInput>().is_err());
+        assert!("{x} * {y}".parse::<FnInput>().is_err());
+        assert!("[]".parse::<FnInput>().is_err());
+        assert!("(-0.5, 0.5): 1; else: 0".parse::<FnInput>().is_ok());
+        assert!("(-0.5, 0.5): {x} * {y}".parse::<FnInput>().is_err());
+        assert!("else: 0; (-0.5, 0.5): 1".parse::<FnInput>().is_err());
+        assert!("(0.5, -0.5): 1".parse::<FnInput>().is_err());
+    }
+
+    #[test]
+    fn piecewise() -> anyhow::Result<()> {
+        let fn_input: FnInput = "[-1, 0): 1 + {x}; [0, 1]: 1 - {x}; else: -1".parse()?;
+        assert_eq!(fn_input.eval(-0.5), 0.5);
+        assert_eq!(fn_input.eval(0.), 1.);
+        assert_eq!(fn_input.eval(1.), 0.);
+        assert_eq!(fn_input.eval(2.), -1.);
+
+        let string: String = ron::ser::to_string(&fn_input)?;
+        let other_fn_input: FnInput = ron::de::from_str(&string)?;
+        assert_eq!(fn_input.string, other_fn_input.string);
+        assert_eq!(other_fn_input.eval(-0.5), 0.5);
+
+        let fn_input: FnInput = "(-0.5, 0.5): 1".parse()?;
+        assert_eq!(fn_input.eval(0.5), 0.);
+        assert_eq!(fn_input.eval(0.), 1.);
+
+        // A trailing separator is accepted after any piece
+        let fn_input: FnInput = "(-0.5, 0.5): 1;".parse()?;
+        assert_eq!(fn_input.eval(0.), 1.);
+        let fn_input: FnInput = "(-0.5, 0.5): 1; else: 2;".parse()?;
+        assert_eq!(fn_input.eval(1.), 2.);
+        assert!("(-0.5, 0.5): 1; ;".parse::<FnInput>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn constant() -> anyhow::Result<()> {
+        let fn_input: FnInput = "0".parse()?;
+        assert_eq!(fn_input.eval(0.5), 0.);
+        let fn_input: FnInput = "2 * 3".parse()?;
+        assert_eq!(fn_input.eval(-1.), 6.);
+        Ok(())
+    }
 }
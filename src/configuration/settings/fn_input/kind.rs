@@ -1,20 +1,27 @@
 use core::fmt::Display;
 use core::str::FromStr;
 use exmex::FlatEx;
-use splines::{interpolation::Interpolation, key::Key, spline::Spline};
+use splines::spline::Spline;
 use thiserror::Error;
 
+use crate::configuration::settings::InterpolationKind;
+
+pub mod piecewise;
+use piecewise::Piecewise;
+
 #[derive(Debug, Clone)]
 pub enum FnInputKind {
-    Analytical { expression: FlatEx<f64> },
+    Analytical { expression: Box<FlatEx<f64>> },
     Points { spline: Spline<f64, f64> },
+    Piecewise { piecewise: Piecewise },
 }
 impl Default for FnInputKind {
     fn default() -> Self {
         let string = "sin({x})".to_string();
         FnInputKind::Analytical {
-            expression: exmex::parse::<f64>(&string, &exmex::make_default_operators::<f64>())
-                .unwrap(),
+            expression: Box::new(
+                exmex::parse::<f64>(&string, &exmex::make_default_operators::<f64>()).unwrap(),
+            ),
         }
     }
 }
@@ -25,7 +32,8 @@ impl Display for FormatError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
         write!(
             f,
-            "The input was not an anlytical function nor a collection of points."
+            "The input `{}` was not an analytical function of one variable, a piecewise function nor a collection of points.",
+            self.0
         )
     }
 }
@@ -33,22 +41,46 @@ impl Display for FormatError {
 impl FromStr for FnInputKind {
     type Err = FormatError;
     fn from_str(s: &str) -> Result<Self, FormatError> {
-        if let Ok(expression) = exmex::parse(s, &exmex::make_default_operators::<f64>()) {
+        if let Some(expression) = parse_expression(s) {
             log::debug!("We noticed an analyical function :)");
-            Ok(FnInputKind::Analytical { expression })
+            Ok(FnInputKind::Analytical {
+                expression: Box::new(expression),
+            })
+        } else if let Ok(piecewise) = s.parse::<Piecewise>() {
+            log::debug!("We noticed a piecewise function");
+            Ok(FnInputKind::Piecewise { piecewise })
         } else {
             log::debug!("We noticed it was not an analyical function.");
-            if let Ok(values) = ron::de::from_str::<Vec<(f64, f64)>>(s) {
-                let spline = Spline::from_iter(
-                    values
-                        .iter()
-                        .map(|(x, y)| Key::new(*x, *y, Interpolation::Cosine)),
-                );
-                log::debug!("We noticed a collection of points");
-                Ok(FnInputKind::Points { spline })
-            } else {
-                Err(FormatError(s.to_string()))
+            match ron::de::from_str::<Vec<(f64, f64)>>(s) {
+                Ok(values) if !values.is_empty() => {
+                    let spline = InterpolationKind::default().spline(values);
+                    log::debug!("We noticed a collection of points");
+                    Ok(FnInputKind::Points { spline })
+                }
+                _ => Err(FormatError(s.to_string())),
             }
         }
     }
 }
+
+/// Parses an analytical function of at most one variable.
+fn parse_expression(s: &str) -> Option<FlatEx<f64>> {
+    let expression = exmex::parse(s, &exmex::make_default_operators::<f64>()).ok()?;
+    if expression.eval(&[0.]).is_err() && expression.eval(&[]).is_err() {
+        log::debug!("The analytical function depends on more than one variable.");
+        return None;
+    }
+    Some(expression)
+}
+
+/// Evaluates an analytical function of at most one variable.
+///
+/// # Panics
+///
+/// If the function depends on more than one variable, see `parse_expression`.
+pub fn eval_expression(expression: &FlatEx<f64>, x: f64) -> f64 {
+    expression
+        .eval(&[x])
+        .or_else(|_| expression.eval(&[]))
+        .unwrap()
+}
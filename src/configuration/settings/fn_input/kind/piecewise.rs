@@ -0,0 +1,134 @@
+//! Piecewise-defined functions
+//!
+//! The syntax is a list of pieces separated by `;`, each of them an interval and an
+//! analytical function separated by `:`. The last piece may be `else: <function>`,
+//! which is used outside of every interval. For example,
+//!
+//! ```text
+//! (-0.5, 0.5): 1; else: 0
+//! [-1, 0): 1 + {x}; [0, 1]: 1 - {x}
+//! ```
+//!
+//! Intervals can be open or closed at each end and `inf` can be used as a bound.
+//! If several intervals contain a point, the first one is used. Points not covered by
+//! any interval evaluate to zero, unless an `else` piece is given.
+
+use core::str::FromStr;
+use exmex::FlatEx;
+
+use super::{eval_expression, parse_expression, FormatError};
+
+/// Keyword for the piece used outside of every interval.
+const OTHERWISE: &str = "else";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub start: f64,
+    pub end: f64,
+    pub closed_start: bool,
+    pub closed_end: bool,
+}
+
+impl Interval {
+    pub fn contains(&self, x: f64) -> bool {
+        let after_start = if self.closed_start {
+            self.start <= x
+        } else {
+            self.start < x
+        };
+        let before_end = if self.closed_end {
+            x <= self.end
+        } else {
+            x < self.end
+        };
+        after_start && before_end
+    }
+}
+
+impl FromStr for Interval {
+    type Err = FormatError;
+    fn from_str(s: &str) -> Result<Self, FormatError> {
+        let error = || FormatError(s.to_string());
+        let s = s.trim();
+        let closed_start = match s.chars().next() {
+            Some('[') => true,
+            Some('(') => false,
+            _ => return Err(error()),
+        };
+        let closed_end = match s.chars().last() {
+            Some(']') => true,
+            Some(')') => false,
+            _ => return Err(error()),
+        };
+        let (start, end) = s[1..s.len() - 1].split_once(',').ok_or_else(error)?;
+        let start: f64 = start.trim().parse().map_err(|_| error())?;
+        let end: f64 = end.trim().parse().map_err(|_| error())?;
+        if start.is_nan() || end.is_nan() || start > end {
+            return Err(error());
+        }
+        Ok(Interval {
+            start,
+            end,
+            closed_start,
+            closed_end,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Piece {
+    pub interval: Interval,
+    pub expression: Box<FlatEx<f64>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Piecewise {
+    pub pieces: Vec<Piece>,
+    pub otherwise: Option<Box<FlatEx<f64>>>,
+}
+
+impl Piecewise {
+    /// Evaluates the function at a given value.
+    pub fn eval(&self, x: f64) -> f64 {
+        match self.pieces.iter().find(|piece| piece.interval.contains(x)) {
+            Some(piece) => eval_expression(&piece.expression, x),
+            None => match &self.otherwise {
+                Some(expression) => eval_expression(expression, x),
+                None => 0.,
+            },
+        }
+    }
+}
+
+impl FromStr for Piecewise {
+    type Err = FormatError;
+    fn from_str(s: &str) -> Result<Self, FormatError> {
+        let error = || FormatError(s.to_string());
+        let mut pieces = vec![];
+        let mut otherwise = None;
+        let mut parts: Vec<&str> = s.split(';').map(str::trim).collect();
+        if parts.last() == Some(&"") {
+            parts.pop(); // trailing separator
+        }
+        let mut parts = parts.into_iter().peekable();
+        while let Some(part) = parts.next() {
+            let (domain, expression) = part.split_once(':').ok_or_else(error)?;
+            let expression = parse_expression(expression.trim()).ok_or_else(error)?;
+            if domain.trim() == OTHERWISE {
+                if parts.peek().is_some() {
+                    return Err(error());
+                }
+                otherwise = Some(Box::new(expression));
+            } else {
+                pieces.push(Piece {
+                    interval: domain.parse().map_err(|_| error())?,
+                    expression: Box::new(expression),
+                });
+            }
+        }
+        if pieces.is_empty() {
+            return Err(error());
+        }
+        Ok(Piecewise { pieces, otherwise })
+    }
+}
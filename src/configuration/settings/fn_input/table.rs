@@ -0,0 +1,125 @@
+//! Tables of values, read from CSV or TSV files
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum TableError {
+    #[error("The file is not valid text")]
+    Encoding,
+    #[error("The file has no values")]
+    Empty,
+    #[error("The file needs at least two columns, found {0}")]
+    TooFewColumns(usize),
+    #[error("Line {line}: expected {expected} values, found {found}")]
+    Columns {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error("Line {line}: `{value}` is not a number")]
+    NotANumber { line: usize, value: String },
+}
+
+/// Numerical columns, with their names.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub columns: Vec<Vec<f64>>,
+}
+
+impl Table {
+    /// Parses comma, semicolon or tab separated values.
+    ///
+    /// The delimiter is detected from the first line, which is taken as a header
+    /// if it is not numerical. Empty lines and lines starting with `#` are ignored.
+    pub fn parse(content: &str) -> Result<Self, TableError> {
+        let mut lines = content
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .peekable();
+        let delimiter = match lines.peek() {
+            Some((_, line)) if line.contains('\t') => '\t',
+            Some((_, line)) if line.contains(';') => ';',
+            Some(_) => ',',
+            None => return Err(TableError::Empty),
+        };
+        let split = |line: &str| -> Vec<String> {
+            line.split(delimiter)
+                .map(|value| value.trim().trim_matches('"').to_string())
+                .collect()
+        };
+
+        let (_, first) = lines.peek().unwrap();
+        let first = split(first);
+        let headers = if first.iter().any(|value| value.parse::<f64>().is_err()) {
+            lines.next();
+            first
+        } else {
+            (1..=first.len()).map(|i| format!("Column {}", i)).collect()
+        };
+        if headers.len() < 2 {
+            return Err(TableError::TooFewColumns(headers.len()));
+        }
+
+        let mut columns = vec![vec![]; headers.len()];
+        for (line, values) in lines {
+            let values = split(values);
+            if values.len() != headers.len() {
+                return Err(TableError::Columns {
+                    line,
+                    expected: headers.len(),
+                    found: values.len(),
+                });
+            }
+            for (column, value) in columns.iter_mut().zip(values) {
+                let number = value
+                    .parse()
+                    .map_err(|_| TableError::NotANumber { line, value })?;
+                column.push(number);
+            }
+        }
+        if columns[0].is_empty() {
+            return Err(TableError::Empty);
+        }
+
+        Ok(Table { headers, columns })
+    }
+
+    /// Pairs of values of the columns `x` and `y`.
+    pub fn points(&self, x: usize, y: usize) -> Vec<(f64, f64)> {
+        self.columns[x]
+            .iter()
+            .copied()
+            .zip(self.columns[y].iter().copied())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() -> anyhow::Result<()> {
+        let table = Table::parse("x,u,v\n0,1,2\n\n# comment\n1,3.5,4\n")?;
+        assert_eq!(table.headers, vec!["x", "u", "v"]);
+        assert_eq!(table.points(0, 2), vec![(0., 2.), (1., 4.)]);
+
+        let table = Table::parse("0\t1\n1\t3.5")?;
+        assert_eq!(table.headers, vec!["Column 1", "Column 2"]);
+        assert_eq!(table.points(0, 1), vec![(0., 1.), (1., 3.5)]);
+
+        assert!(matches!(
+            Table::parse("x;u\n0;1\n1;a"),
+            Err(TableError::NotANumber { line: 3, .. })
+        ));
+        assert!(matches!(
+            Table::parse("x,u\n0,1,2"),
+            Err(TableError::Columns { line: 2, .. })
+        ));
+        assert!(matches!(Table::parse("x,u\n"), Err(TableError::Empty)));
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use core::cmp::Ordering;
+use serde::{Deserialize, Serialize};
+use splines::{Interpolation, Key, Spline};
+
+/// Interpolation between the points of a spline.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Deserialize,
+    Serialize,
+    strum::Display,
+    strum::EnumIter,
+    strum::EnumString,
+)]
+pub enum InterpolationKind {
+    /// Nearest point
+    Step,
+    Linear,
+    #[default]
+    Cosine,
+    CatmullRom,
+}
+
+impl InterpolationKind {
+    pub fn interpolation(&self) -> Interpolation<f64, f64> {
+        match self {
+            InterpolationKind::Step => Interpolation::Step(0.5),
+            InterpolationKind::Linear => Interpolation::Linear,
+            InterpolationKind::Cosine => Interpolation::Cosine,
+            InterpolationKind::CatmullRom => Interpolation::CatmullRom,
+        }
+    }
+
+    /// Builds a spline through `points`.
+    ///
+    /// Catmull-Rom needs two points on each side, so the first and last segments
+    /// are interpolated linearly instead.
+    pub fn spline<I>(&self, points: I) -> Spline<f64, f64>
+    where
+        I: IntoIterator<Item = (f64, f64)>,
+    {
+        let mut keys: Vec<Key<f64, f64>> = points
+            .into_iter()
+            .map(|(x, y)| Key::new(x, y, self.interpolation()))
+            .collect();
+        keys.sort_by(|a, b| a.t.partial_cmp(&b.t).unwrap_or(Ordering::Equal));
+        if let InterpolationKind::CatmullRom = self {
+            let len = keys.len();
+            for i in [0, len.saturating_sub(2)] {
+                if let Some(key) = keys.get_mut(i) {
+                    key.interpolation = Interpolation::Linear;
+                }
+            }
+        }
+        Spline::from_vec(keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn sample_everywhere() {
+        let points: Vec<(f64, f64)> = (0..5).map(|i| (i as f64, (i * i) as f64)).collect();
+        for interpolation in InterpolationKind::iter() {
+            let spline = interpolation.spline(points.clone());
+            for x in itertools_num::linspace(-1., 5., 25) {
+                assert!(
+                    spline.clamped_sample(x).is_some(),
+                    "{} at {}",
+                    interpolation,
+                    x
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Built-in initial conditions
+
+use core::f64::consts::PI;
+use itertools::Itertools;
+
+use crate::configuration::settings::SettingsError;
+
+/// Named initial conditions, adapted to the domain.
+#[derive(Debug, Clone, Copy, PartialEq, strum::Display, strum::EnumIter, strum::EnumString)]
+pub enum Preset {
+    #[strum(serialize = "Gaussian bump")]
+    GaussianBump,
+    #[strum(serialize = "Step function")]
+    Step,
+    #[strum(serialize = "Square wave")]
+    SquareWave,
+    #[strum(serialize = "Triangle")]
+    Triangle,
+    #[strum(serialize = "Dirac approximation")]
+    DiracApproximation,
+    #[strum(serialize = "Sum of sines")]
+    SumOfSines,
+    #[strum(serialize = "Random noise")]
+    RandomNoise,
+}
+
+impl Preset {
+    /// Name and default value of the parameters of the preset, if any.
+    pub fn parameters(&self) -> Option<(&'static str, &'static str)> {
+        match self {
+            Preset::SumOfSines => Some(("Modes", "1, 3, 5")),
+            Preset::RandomNoise => Some(("Seed", "42")),
+            _ => None,
+        }
+    }
+
+    /// Initial conditions in the same format a user would type them.
+    ///
+    /// # Errors
+    ///
+    /// If `parameters` are not valid for this preset.
+    pub fn fn_input_string(
+        &self,
+        domain: (f64, f64),
+        quality: usize,
+        parameters: &str,
+    ) -> Result<String, SettingsError> {
+        let (a, b) = domain;
+        let width = b - a;
+        let center = (a + b) / 2.;
+        let string = match self {
+            Preset::GaussianBump => {
+                let std_dev = width / 8.;
+                format!("exp(-1 * (({{x}} - {}) / {})^2 / 2)", center, std_dev)
+            }
+            Preset::Step => format!("[{}, inf): 1; else: 0", center),
+            Preset::SquareWave => {
+                let frequency = 8. * PI / width;
+                format!("(1 + signum(sin({} * ({{x}} - {})))) / 2", frequency, a)
+            }
+            Preset::Triangle => {
+                let height = center - a;
+                format!(
+                    "[{a}, {c}): ({{x}} - {a}) / {h}; [{c}, {b}]: ({b} - {{x}}) / {h}",
+                    a = a,
+                    b = b,
+                    c = center,
+                    h = height
+                )
+            }
+            Preset::DiracApproximation => {
+                let epsilon = width / 50.;
+                format!(
+                    "exp(-1 * (({{x}} - {}) / {})^2) * {}",
+                    center,
+                    epsilon,
+                    (epsilon * PI.sqrt()).recip()
+                )
+            }
+            Preset::SumOfSines => {
+                let modes: Vec<u32> = parameters
+                    .split(',')
+                    .map(|mode| mode.trim().parse())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| SettingsError::PresetParameters(parameters.to_string()))?;
+                modes
+                    .iter()
+                    .map(|k| format!("sin({} * ({{x}} - {}))", *k as f64 * PI / width, a))
+                    .join(" + ")
+            }
+            Preset::RandomNoise => {
+                let seed: u64 = parameters
+                    .trim()
+                    .parse()
+                    .map_err(|_| SettingsError::PresetParameters(parameters.to_string()))?;
+                let mut rng = XorShift::new(seed);
+                let points = itertools_num::linspace(a, b, quality)
+                    .map(|x| format!("({}, {:.4})", x, rng.next_f64()))
+                    .join(", ");
+                format!("[{}]", points)
+            }
+        };
+        Ok(string)
+    }
+}
+
+/// Minimal pseudo-random number generator, so that noise is reproducible from a seed.
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        // The state must not be zero
+        XorShift((seed ^ 0x9E37_79B9_7F4A_7C15).max(1))
+    }
+
+    /// Uniform sample in [0, 1).
+    fn next_f64(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configuration::settings::FnInput;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn valid_fn_inputs() -> anyhow::Result<()> {
+        for preset in Preset::iter() {
+            let parameters = preset.parameters().map(|(_, p)| p).unwrap_or_default();
+            let fn_input: FnInput = preset.fn_input_string((-1., 1.), 10, parameters)?.parse()?;
+            assert!(fn_input.eval(0.3).is_finite(), "{}", preset);
+        }
+        assert!(Preset::SumOfSines
+            .fn_input_string((-1., 1.), 10, "1, a")
+            .is_err());
+        Ok(())
+    }
+}
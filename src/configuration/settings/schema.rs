@@ -0,0 +1,308 @@
+//! Versioned schema of the settings
+//!
+//! Settings are written as RON by their `Serialize` implementation, including the version of
+//! the schema; settings without version are from version 1. When reading, the fields of older
+//! versions are migrated to the current one and then read one by one, so that a field that
+//! can not be read, or whose value is not valid, is reset to its default value and reported,
+//! keeping every other field.
+
+use ron::value::RawValue;
+use serde::de::{DeserializeOwned, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::{collections::BTreeMap, fmt};
+use thiserror::Error;
+
+use crate::configuration::settings::{Settings, SettingsError};
+
+pub const SCHEMA_VERSION: u32 = 3;
+
+/// Migrations from each version to the next one, starting from version 1.
+const MIGRATIONS: [fn(&mut Fields); (SCHEMA_VERSION - 1) as usize] = [v1_to_v2, v2_to_v3];
+
+/// Fields of a RON structure, each of them as RON.
+type Fields = BTreeMap<String, String>;
+
+#[derive(Error, Debug)]
+pub enum SchemaError {
+    #[error("The settings are not a RON structure")]
+    NotAStructure,
+    #[error("Invalid schema version `{0}`")]
+    Version(String),
+    #[error(
+        "The settings have schema version {0}, but only up to {} is supported",
+        SCHEMA_VERSION
+    )]
+    Newer(u32),
+}
+
+/// A field that could not be read or was not valid.
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("`{field}` could not be read or was not valid, and was reset ({message})")]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Settings read from RON, with the fields that had to be reset.
+#[derive(Debug, Clone)]
+pub struct Recovered {
+    pub settings: Settings,
+    pub errors: Vec<FieldError>,
+}
+
+impl From<Settings> for Recovered {
+    fn from(settings: Settings) -> Self {
+        Recovered {
+            settings,
+            errors: vec![],
+        }
+    }
+}
+
+impl Settings {
+    /// Reads settings written by any version of the schema.
+    ///
+    /// # Errors
+    ///
+    /// If `ron` is not a structure or is from a newer version. Fields that can not be read or
+    /// are not valid are not errors, but are reported in the output.
+    pub fn from_ron(ron: &str) -> Result<Recovered, SchemaError> {
+        let Recovered {
+            mut settings,
+            mut errors,
+        } = Settings::read_ron(ron)?;
+        let default = Settings::default();
+        let e = &mut errors;
+        let result = settings.validate_quality();
+        reset(e, "quality", result, &mut settings.quality, default.quality);
+        let result = settings.validate_kernel();
+        reset(e, "kernel", result, &mut settings.kernel, default.kernel);
+        let result = settings.validate_domain();
+        reset(e, "domain", result, &mut settings.domain, default.domain);
+        Ok(Recovered { settings, errors })
+    }
+
+    /// Reads settings written by any version of the schema, without validating their values.
+    pub(super) fn read_ron(ron: &str) -> Result<Recovered, SchemaError> {
+        let mut fields = split_fields(ron)?;
+        migrate(&mut fields)?;
+
+        let mut settings = Settings::default();
+        let mut errors = vec![];
+        let f = &mut fields;
+        let e = &mut errors;
+        read(f, e, "initial_conditions", &mut settings.initial_conditions);
+        read(f, e, "domain", &mut settings.domain);
+        read(f, e, "border_conditions", &mut settings.border_conditions);
+        read(f, e, "quality", &mut settings.quality);
+        read(f, e, "kernel", &mut settings.kernel);
+        read(f, e, "interpolation", &mut settings.interpolation);
+        for field in fields.keys() {
+            log::warn!("Ignoring unknown setting {}", field);
+        }
+
+        Ok(Recovered { settings, errors })
+    }
+}
+
+/// Migrates `fields` from their version to the current one, removing the version.
+fn migrate(fields: &mut Fields) -> Result<(), SchemaError> {
+    let version = match fields.remove("version") {
+        Some(version) => match version.trim().parse() {
+            Ok(version) if version >= 1 => version,
+            _ => return Err(SchemaError::Version(version)),
+        },
+        None => 1,
+    };
+    if version > SCHEMA_VERSION {
+        return Err(SchemaError::Newer(version));
+    }
+    for migration in &MIGRATIONS[(version - 1) as usize..] {
+        migration(fields);
+    }
+    Ok(())
+}
+
+/// Reads `field` into `value`, keeping `value` and reporting in `errors` if it fails.
+fn read<T: DeserializeOwned>(
+    fields: &mut Fields,
+    errors: &mut Vec<FieldError>,
+    field: &str,
+    value: &mut T,
+) {
+    if let Some(ron) = fields.remove(field) {
+        match ron::de::from_str(&ron) {
+            Ok(v) => *value = v,
+            Err(e) => {
+                log::warn!("Could not read {} from {}: {}", field, ron, e);
+                errors.push(FieldError {
+                    field: field.to_string(),
+                    message: e.to_string(),
+                });
+            }
+        }
+    }
+}
+
+/// Resets `value` to `default` and reports in `errors` if it is not valid.
+fn reset<T>(
+    errors: &mut Vec<FieldError>,
+    field: &str,
+    validation: Result<(), SettingsError>,
+    value: &mut T,
+    default: T,
+) {
+    if let Err(e) = validation {
+        log::warn!("Invalid {}: {}", field, e);
+        errors.push(FieldError {
+            field: field.to_string(),
+            message: e.to_string(),
+        });
+        *value = default;
+    }
+}
+
+/// Version 2 made the interpolation selectable, which used to be always cosine.
+fn v1_to_v2(fields: &mut Fields) {
+    fields
+        .entry("interpolation".to_string())
+        .or_insert_with(|| "Cosine".to_string());
+}
+
+/// Version 3 left the size of the plots out, as it follows the page.
+fn v2_to_v3(fields: &mut Fields) {
+    fields.remove("canvas_size");
+}
+
+/// Splits a RON structure, like `Name(a: 1, b: (2, 3))`, into its fields.
+fn split_fields(ron: &str) -> Result<Fields, SchemaError> {
+    let structure: Structure = ron::de::from_str(ron).map_err(|_| SchemaError::NotAStructure)?;
+    Ok(structure.0)
+}
+
+/// Fields of a RON structure, each kept as written.
+struct Structure(Fields);
+
+impl<'de> Deserialize<'de> for Structure {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(StructureVisitor)
+    }
+}
+
+struct StructureVisitor;
+
+impl<'de> Visitor<'de> for StructureVisitor {
+    type Value = Structure;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a structure with named fields")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut fields = Fields::new();
+        while let Some((field, value)) = map.next_entry::<String, Box<RawValue>>()? {
+            fields.insert(field, value.trim().get_ron().to_string());
+        }
+        Ok(Structure(fields))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Settings as stored by version 1
+    const V1: &str = r#"(initial_conditions:(string:"[(-1, 0.5), (1, 2)]"),domain:(-1.0,1.0),border_conditions:Periodic,quality:42,kernel:Heat(time_step:1.0,std_dev_inv:1.0),canvas_size:(360,360))"#;
+
+    #[test]
+    fn round_trip() -> anyhow::Result<()> {
+        let settings = Settings {
+            quality: 42,
+            initial_conditions: "(-0.5, 0.5): 1; else: 0".parse()?,
+            ..Default::default()
+        };
+        let recovered = Settings::from_ron(&ron::ser::to_string(&settings)?)?;
+        assert!(recovered.errors.is_empty());
+        assert!(recovered.settings.same(&settings));
+        assert!(!recovered.settings.same(&Settings::default()));
+        assert_eq!(recovered.settings.quality, 42);
+        assert_eq!(
+            recovered.settings.initial_conditions.string,
+            settings.initial_conditions.string
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn migrate() -> anyhow::Result<()> {
+        let recovered = Settings::from_ron(V1)?;
+        assert!(recovered.errors.is_empty());
+        assert_eq!(recovered.settings.quality, 42);
+        assert_eq!(recovered.settings.version, SCHEMA_VERSION);
+
+        // Version 2 still had the size of the plots
+        let v2 = r#"(version:2,quality:42,interpolation:Linear,canvas_size:(360,360))"#;
+        let mut fields = split_fields(v2)?;
+        super::migrate(&mut fields)?;
+        let names: Vec<&str> = fields.keys().map(String::as_str).collect();
+        assert_eq!(names, vec!["interpolation", "quality"]);
+        let recovered = Settings::from_ron(v2)?;
+        assert!(recovered.errors.is_empty());
+        assert_eq!(recovered.settings.quality, 42);
+
+        // Named, with a comment and a raw string, whose quotes make only its field invalid
+        let named = V1
+            .replacen('(', "Settings( // initial conditions, then (the rest)\n", 1)
+            .replace(
+                r#""[(-1, 0.5), (1, 2)]""#,
+                r##"r#"(-1, 0): "1"; else: 0"#"##,
+            );
+        let recovered = Settings::from_ron(&named)?;
+        assert_eq!(recovered.errors.len(), 1);
+        assert_eq!(recovered.errors[0].field, "initial_conditions");
+        assert_eq!(recovered.settings.quality, 42);
+        Ok(())
+    }
+
+    #[test]
+    fn recover() -> anyhow::Result<()> {
+        let ron = V1
+            .replace("[(-1, 0.5), (1, 2)]", "sin({x}")
+            .replace("42", "-3");
+        let recovered = Settings::from_ron(&ron)?;
+        let fields: Vec<&str> = recovered.errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["initial_conditions", "quality"]);
+        assert_eq!(recovered.settings.domain, (-1., 1.));
+
+        // Values that can be read but are not valid are reset too
+        let recovered = Settings::from_ron(&V1.replace("42", "1"))?;
+        let fields: Vec<&str> = recovered.errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["quality"]);
+        assert_eq!(recovered.settings.quality, Settings::default().quality);
+        let recovered = Settings::from_ron(
+            &V1.replace("(-1.0,1.0)", "(1.0,-1.0)")
+                .replace("time_step:1.0", "time_step:-1.0"),
+        )?;
+        let fields: Vec<&str> = recovered.errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["kernel", "domain"]);
+        assert_eq!(recovered.settings.domain, Settings::default().domain);
+        assert_eq!(
+            recovered.settings.kernel.time_step(),
+            Settings::default().kernel.time_step()
+        );
+        let recovered = Settings::from_ron(&V1.replace("std_dev_inv:1.0", "std_dev_inv:-1.0"))?;
+        assert_eq!(recovered.errors.len(), 1);
+        assert_eq!(recovered.errors[0].field, "kernel");
+        assert_eq!(
+            recovered.settings.kernel.std_dev_inv(),
+            Settings::default().kernel.std_dev_inv()
+        );
+
+        assert!(matches!(
+            Settings::from_ron("(version: 99)"),
+            Err(SchemaError::Newer(99))
+        ));
+        assert!(Settings::from_ron("[1, 2]").is_err());
+        Ok(())
+    }
+}
@@ -1,56 +1,249 @@
 //! Storage implementation
+//!
+//! Everything is stored in local storage under a single key, together with the version of
+//! the layout so that older layouts can be migrated. Each settings is stored as its own RON
+//! string, so that an unreadable entry does not prevent reading the others.
 
-use crate::configuration::settings::Settings;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use web_sys::Storage as LocalStorage;
 
-const KEY: &'static str = "yew.heat_eq.settings";
+use crate::configuration::settings::{Recovered, Settings};
 
-impl Settings {
-    pub fn remove_and_default() -> Self {
-        log::trace!("Removing values from storage");
-        match Settings::remove() {
-            Ok(()) => Settings::default(),
-            Err(_) => {
-                log::error!("Could not remove value!");
-                Settings::default()
+const KEY: &str = "yew.heat_eq.storage";
+/// Key of the first layout, which only kept the last settings.
+const LEGACY_KEY: &str = "yew.heat_eq.settings";
+const LAYOUT_VERSION: u32 = 1;
+
+/// Contents of the storage.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Storage {
+    pub version: u32,
+    /// Last settings sent to the analysis.
+    pub current: Option<String>,
+    /// Saved settings, by name.
+    pub scenarios: BTreeMap<String, String>,
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        Self {
+            version: LAYOUT_VERSION,
+            current: None,
+            scenarios: BTreeMap::new(),
+        }
+    }
+}
+
+impl Storage {
+    fn local_storage() -> LocalStorage {
+        web_sys::window().unwrap().local_storage().unwrap().unwrap()
+    }
+
+    /// Reads the storage, migrating older layouts if needed.
+    pub fn load() -> anyhow::Result<Self> {
+        let local_storage = Storage::local_storage();
+        let stored = local_storage
+            .get_item(KEY)
+            .map_err(|_| anyhow::anyhow!("Could not get {}", KEY))?;
+        match stored {
+            Some(ron) => Storage::from_ron(&ron),
+            None => {
+                let legacy = local_storage
+                    .get_item(LEGACY_KEY)
+                    .map_err(|_| anyhow::anyhow!("Could not get {}", LEGACY_KEY))?;
+                let storage = Storage::migrate_legacy(legacy);
+                if storage.current.is_some() {
+                    log::info!("Migrating storage from {} to {}", LEGACY_KEY, KEY);
+                    storage.save()?;
+                    local_storage
+                        .remove_item(LEGACY_KEY)
+                        .map_err(|_| anyhow::anyhow!("Failed to remove {}", LEGACY_KEY))?;
+                }
+                Ok(storage)
             }
         }
     }
 
-    pub fn restore_or_default() -> Self {
+    pub fn save(&self) -> anyhow::Result<()> {
+        Storage::local_storage()
+            .set_item(KEY, &ron::ser::to_string(self)?)
+            .map_err(|_| anyhow::anyhow!("Failed to store"))?;
+        Ok(())
+    }
+
+    /// Reads, changes and writes back the storage.
+    pub fn modify<F, T>(f: F) -> anyhow::Result<T>
+    where
+        F: FnOnce(&mut Storage) -> anyhow::Result<T>,
+    {
+        let mut storage = Storage::load()?;
+        let output = f(&mut storage)?;
+        storage.save()?;
+        Ok(output)
+    }
+
+    pub fn from_ron(ron: &str) -> anyhow::Result<Self> {
+        let storage: Storage = ron::de::from_str(ron)?;
+        if storage.version > LAYOUT_VERSION {
+            anyhow::bail!(
+                "The storage has layout version {}, but only up to {} is supported",
+                storage.version,
+                LAYOUT_VERSION
+            );
+        }
+        Ok(storage)
+    }
+
+    /// The first layout stored only the RON of the last settings.
+    fn migrate_legacy(legacy: Option<String>) -> Self {
+        Storage {
+            current: legacy,
+            ..Default::default()
+        }
+    }
+
+    pub fn scenario_names(&self) -> Vec<String> {
+        self.scenarios.keys().cloned().collect()
+    }
+
+    pub fn save_scenario(&mut self, name: &str, settings: &Settings) -> anyhow::Result<()> {
+        let name = name.trim();
+        if name.is_empty() {
+            anyhow::bail!("The scenario needs a name");
+        }
+        self.scenarios
+            .insert(name.to_string(), ron::ser::to_string(settings)?);
+        Ok(())
+    }
+
+    /// Reads the last settings sent to the analysis, resetting the fields that are not valid.
+    pub fn load_current(&self) -> anyhow::Result<Recovered> {
+        let ron = self
+            .current
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Could not get {}", KEY))?;
+        Ok(Settings::from_ron(ron)?)
+    }
+
+    /// Reads a saved scenario, resetting the fields that are not valid.
+    pub fn load_scenario(&self, name: &str) -> anyhow::Result<Recovered> {
+        let ron = self
+            .scenarios
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("There is no scenario named {}", name))?;
+        Ok(Settings::from_ron(ron)?)
+    }
+
+    pub fn rename_scenario(&mut self, name: &str, new_name: &str) -> anyhow::Result<()> {
+        self.duplicate_scenario(name, new_name)?;
+        self.scenarios.remove(name);
+        Ok(())
+    }
+
+    pub fn duplicate_scenario(&mut self, name: &str, new_name: &str) -> anyhow::Result<()> {
+        let new_name = new_name.trim();
+        if new_name.is_empty() {
+            anyhow::bail!("The scenario needs a name");
+        }
+        if self.scenarios.contains_key(new_name) {
+            anyhow::bail!("There is already a scenario named {}", new_name);
+        }
+        let ron = self
+            .scenarios
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("There is no scenario named {}", name))?
+            .clone();
+        self.scenarios.insert(new_name.to_string(), ron);
+        Ok(())
+    }
+
+    pub fn delete_scenario(&mut self, name: &str) -> anyhow::Result<()> {
+        self.scenarios
+            .remove(name)
+            .ok_or_else(|| anyhow::anyhow!("There is no scenario named {}", name))?;
+        Ok(())
+    }
+}
+
+impl Settings {
+    pub fn restore_or_default() -> Recovered {
         log::trace!("Restoring values from storage");
         match Settings::restore() {
-            Ok(settings) => settings,
-            Err(_) => {
-                log::warn!("There are no records, we set settings to default");
-                Settings::default()
+            Ok(recovered) => recovered,
+            Err(e) => {
+                log::warn!("There are no records, we set settings to default: {}", e);
+                Settings::default().into()
             }
         }
     }
 
-    pub fn remove() -> anyhow::Result<()> {
-        let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
-        local_storage
-            .remove_item(KEY)
-            .map_err(|_| anyhow::anyhow!("Failed to remove"))?;
-        Ok(())
+    pub fn restore() -> anyhow::Result<Recovered> {
+        Storage::load()?.load_current()
     }
 
-    pub fn restore() -> anyhow::Result<Self> {
-        let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
-        let settings = ron::de::from_str(
-            &local_storage
-                .get_item(KEY)
-                .map_err(|_| anyhow::anyhow!("Could not get {}", KEY))?
-                .ok_or_else(|| anyhow::anyhow!("Could not get {}", KEY))?,
-        )?;
-        Ok(settings)
+    pub fn store(&self) -> anyhow::Result<()> {
+        let ron = ron::ser::to_string(self)?;
+        Storage::modify(|storage| {
+            storage.current = Some(ron);
+            Ok(())
+        })
     }
+}
 
-    pub fn store(&self) -> anyhow::Result<()> {
-        let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
-        local_storage
-            .set_item(KEY, &ron::ser::to_string(self)?)
-            .map_err(|_| anyhow::anyhow!("Failed to store"))?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scenarios() -> anyhow::Result<()> {
+        let mut storage = Storage::migrate_legacy(Some(ron::ser::to_string(&Settings::default())?));
+        assert!(storage.current.is_some());
+
+        let settings = Settings {
+            quality: 42,
+            ..Default::default()
+        };
+        storage.save_scenario("a", &settings)?;
+        assert!(storage.save_scenario(" ", &settings).is_err());
+        storage.duplicate_scenario("a", "b")?;
+        assert!(storage.duplicate_scenario("a", "b").is_err());
+        storage.rename_scenario("a", "c")?;
+        assert_eq!(storage.scenario_names(), vec!["b", "c"]);
+        storage.delete_scenario("b")?;
+        assert!(storage.delete_scenario("b").is_err());
+        assert_eq!(storage.load_scenario("c")?.settings.quality, 42);
+
+        let storage = Storage::from_ron(&ron::ser::to_string(&storage)?)?;
+        assert_eq!(storage.scenario_names(), vec!["c"]);
+        let newer = Storage {
+            version: LAYOUT_VERSION + 1,
+            ..Default::default()
+        };
+        assert!(Storage::from_ron(&ron::ser::to_string(&newer)?).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn invalid() -> anyhow::Result<()> {
+        let default = Settings::default();
+        // Legacy settings with a domain that can be read but is not valid
+        let storage = Storage::migrate_legacy(Some("(domain: (1.0, -1.0), quality: 42)".into()));
+        let recovered = storage.load_current()?;
+        assert_eq!(recovered.errors.len(), 1);
+        assert_eq!(recovered.errors[0].field, "domain");
+        assert_eq!(recovered.settings.domain, default.domain);
+        assert_eq!(recovered.settings.quality, 42);
+
+        let mut storage = Storage::default();
+        assert!(storage.load_current().is_err());
+        storage
+            .scenarios
+            .insert("a".into(), "(version: 3, quality: 1)".into());
+        let recovered = storage.load_scenario("a")?;
+        assert_eq!(recovered.errors.len(), 1);
+        assert_eq!(recovered.errors[0].field, "quality");
+        assert_eq!(recovered.settings.quality, default.quality);
         Ok(())
     }
 }
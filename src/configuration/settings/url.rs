@@ -0,0 +1,82 @@
+//! Sharing settings through the URL
+//!
+//! Settings are encoded as compact RON, in URL-safe base64, in the fragment of the URL.
+
+use crate::configuration::settings::{Recovered, Settings};
+
+const PARAMETER: &str = "settings=";
+
+impl Settings {
+    pub fn restore_from_url_or_storage() -> Recovered {
+        log::trace!("Restoring values from the URL");
+        match Settings::restore_from_url() {
+            Ok(recovered) => recovered,
+            Err(e) => {
+                log::debug!("There are no settings in the URL: {}", e);
+                Settings::restore_or_default()
+            }
+        }
+    }
+
+    pub fn restore_from_url() -> anyhow::Result<Recovered> {
+        let hash = web_sys::window()
+            .unwrap()
+            .location()
+            .hash()
+            .map_err(|_| anyhow::anyhow!("Could not get the URL"))?;
+        Settings::from_url_fragment(hash.trim_start_matches('#'))
+    }
+
+    pub fn store_in_url(&self) -> anyhow::Result<()> {
+        web_sys::window()
+            .unwrap()
+            .location()
+            .set_hash(&self.to_url_fragment()?)
+            .map_err(|_| anyhow::anyhow!("Failed to store in the URL"))?;
+        Ok(())
+    }
+
+    pub fn to_url_fragment(&self) -> anyhow::Result<String> {
+        let ron = ron::ser::to_string(self)?;
+        Ok(format!(
+            "{}{}",
+            PARAMETER,
+            base64::encode_config(ron, base64::URL_SAFE_NO_PAD)
+        ))
+    }
+
+    pub fn from_url_fragment(fragment: &str) -> anyhow::Result<Recovered> {
+        let encoded = fragment
+            .strip_prefix(PARAMETER)
+            .ok_or_else(|| anyhow::anyhow!("Could not find {} in {}", PARAMETER, fragment))?;
+        let ron = String::from_utf8(base64::decode_config(encoded, base64::URL_SAFE_NO_PAD)?)?;
+        Ok(Settings::from_ron(&ron)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_fragment() -> anyhow::Result<()> {
+        let settings = Settings {
+            quality: 42,
+            initial_conditions: "(-0.5, 0.5): 1; else: 0".parse()?,
+            ..Default::default()
+        };
+        let fragment = settings.to_url_fragment()?;
+        assert!(fragment
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "=-_".contains(c)));
+
+        let other_settings = Settings::from_url_fragment(&fragment)?.settings;
+        assert_eq!(other_settings.quality, 42);
+        assert_eq!(
+            other_settings.initial_conditions.string,
+            settings.initial_conditions.string
+        );
+        assert!(Settings::from_url_fragment("other=1").is_err());
+        Ok(())
+    }
+}
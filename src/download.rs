@@ -0,0 +1,27 @@
+//! Downloads of files generated in the browser
+
+use wasm_bindgen::JsCast;
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Url};
+
+/// Offers `content` to the user as a file named `file_name`.
+pub fn download(file_name: &str, mime: &str, content: &[u8]) -> anyhow::Result<()> {
+    let bytes = js_sys::Uint8Array::from(content);
+    let options = BlobPropertyBag::new();
+    options.set_type(mime);
+    let blob = Blob::new_with_u8_array_sequence_and_options(&js_sys::Array::of1(&bytes), &options)
+        .map_err(|_| anyhow::anyhow!("Could not create the file {}", file_name))?;
+    let url = Url::create_object_url_with_blob(&blob)
+        .map_err(|_| anyhow::anyhow!("Could not create a link to {}", file_name))?;
+
+    let anchor: HtmlAnchorElement = yew::utils::document()
+        .create_element("a")
+        .map_err(|_| anyhow::anyhow!("Could not create a link to {}", file_name))?
+        .unchecked_into();
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+
+    Url::revoke_object_url(&url)
+        .map_err(|_| anyhow::anyhow!("Could not revoke the link to {}", file_name))?;
+    Ok(())
+}
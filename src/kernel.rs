@@ -55,4 +55,12 @@ impl Kernel {
             } => *time_step,
         }
     }
+    pub fn std_dev_inv(&self) -> f64 {
+        match self {
+            Kernel::Heat {
+                time_step: _,
+                std_dev_inv,
+            } => *std_dev_inv,
+        }
+    }
 }
@@ -4,6 +4,7 @@ use yew::prelude::*;
 
 mod analysis;
 mod configuration;
+mod download;
 mod kernel;
 
 use analysis::Analysis;